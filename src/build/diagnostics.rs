@@ -0,0 +1,230 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// An error or a warning which was reported by the LaTeX engine
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The file in which the issue occurred (if known)
+    pub file: Option<String>,
+    /// The line on which the issue occurred (if known)
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{file}:{line}: {}", self.message),
+            (Some(file), None) => write!(f, "{file}: {}", self.message),
+            (None, Some(line)) => write!(f, "line {line}: {}", self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Extracts errors and warnings from the contents of a LaTeX `.log` file.
+/// Errors are recognized in both the `-file-line-error` (`./file.tex:12: message`)
+/// and the classic (`! message` followed by `l.12`) format
+pub fn parse_log(log: &str) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = log.lines().collect();
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+        let diagnostic = if let Some((file, line_number, message)) = parse_file_line_error(line) {
+            Diagnostic {
+                severity: Severity::Error,
+                file: Some(file.to_string()),
+                line: Some(line_number),
+                message: message.to_string(),
+            }
+        } else if let Some(message) = line.strip_prefix("! ") {
+            // The line number is part of the context which follows the error message
+            let line_number = lines[index..]
+                .iter()
+                .take(10)
+                .find_map(|line| parse_context_line(line));
+            Diagnostic {
+                severity: Severity::Error,
+                file: None,
+                line: line_number,
+                message: message.to_string(),
+            }
+        } else if let Some(message) = parse_warning(line) {
+            // Warnings continue until the next empty line
+            let mut message = message.to_string();
+            while index < lines.len() && !lines[index].trim().is_empty() {
+                let continuation = lines[index].trim_start();
+                // Package warnings prefix their continuation lines with `(package)`
+                let continuation = match continuation.starts_with('(') {
+                    true => continuation
+                        .split_once(')')
+                        .map(|(_, rest)| rest)
+                        .unwrap_or(continuation),
+                    false => continuation,
+                };
+                message.push(' ');
+                message.push_str(continuation.trim());
+                index += 1;
+            }
+            let line_number = message
+                .rsplit_once("on input line ")
+                .and_then(|(_, rest)| rest.trim_end_matches('.').parse().ok());
+            Diagnostic {
+                severity: Severity::Warning,
+                file: None,
+                line: line_number,
+                message: message.trim_end_matches('.').to_string(),
+            }
+        } else {
+            continue;
+        };
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Parses lines like `./content.tex:12: Undefined control sequence.`
+fn parse_file_line_error(line: &str) -> Option<(&str, usize, &str)> {
+    let mut offset = 0;
+    while let Some(position) = line[offset..].find(':') {
+        let colon = offset + position;
+        let rest = &line[colon + 1..];
+        if let Some((number, message)) = rest.split_once(": ") {
+            let file = &line[..colon];
+            if !file.is_empty()
+                && file.contains('.')
+                && !number.is_empty()
+                && number.chars().all(|char| char.is_ascii_digit())
+            {
+                return Some((file, number.parse().ok()?, message));
+            }
+        }
+        offset = colon + 1;
+    }
+    None
+}
+
+/// Parses context lines like `l.12 \foo`
+fn parse_context_line(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix("l.")?;
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// Parses lines like `LaTeX Warning: ...` or `Package hyperref Warning: ...`
+fn parse_warning(line: &str) -> Option<&str> {
+    let (prefix, message) = line.split_once(" Warning: ")?;
+    let is_warning = prefix == "LaTeX"
+        || prefix == "LaTeX Font"
+        || prefix.starts_with("Package ")
+        || prefix.starts_with("Class ");
+    match is_warning {
+        true => Some(message),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(
+        severity: Severity,
+        file: Option<&str>,
+        line: Option<usize>,
+        message: &str,
+    ) -> Diagnostic {
+        Diagnostic {
+            severity,
+            file: file.map(String::from),
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_file_line_errors() {
+        let log = "(./main.tex\n./chapters/intro.tex:12: Undefined control sequence.\nl.12 \\foo\n";
+        assert_eq!(
+            parse_log(log),
+            [diagnostic(
+                Severity::Error,
+                Some("./chapters/intro.tex"),
+                Some(12),
+                "Undefined control sequence."
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_classic_errors() {
+        let log = "! Undefined control sequence.\n<recently read> \\foo\n\nl.7 \\foo\n";
+        assert_eq!(
+            parse_log(log),
+            [diagnostic(
+                Severity::Error,
+                None,
+                Some(7),
+                "Undefined control sequence."
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_multiline_warnings() {
+        let log = "Package hyperref Warning: Token not allowed in a PDF string\n\
+                   (hyperref)                removing `\\textbf' on input line 23.\n\
+                   \n\
+                   LaTeX Warning: There were undefined references.\n\
+                   \n\
+                   LaTeX Info: Redefining \\ref on input line 5.\n";
+        assert_eq!(
+            parse_log(log),
+            [
+                diagnostic(
+                    Severity::Warning,
+                    None,
+                    Some(23),
+                    "Token not allowed in a PDF string removing `\\textbf' on input line 23"
+                ),
+                diagnostic(
+                    Severity::Warning,
+                    None,
+                    None,
+                    "There were undefined references"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_duplicates_and_other_lines() {
+        let log = "This is pdfTeX, Version 3.141592653\n\
+                   Underfull \\hbox (badness 10000) in paragraph at lines 3--4\n\
+                   LaTeX Warning: Reference `fig:a' on page 1 undefined on input line 8.\n\
+                   \n\
+                   LaTeX Warning: Reference `fig:a' on page 1 undefined on input line 8.\n";
+        assert_eq!(
+            parse_log(log),
+            [diagnostic(
+                Severity::Warning,
+                None,
+                Some(8),
+                "Reference `fig:a' on page 1 undefined on input line 8"
+            )]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use super::Error;
+
+/// The commands of the built-in engine presets.
+/// `{main}` is replaced by the main file and `{outdir}` by the output directory
+const PRESETS: [(&str, &str); 5] = [
+    (
+        "latexmk",
+        "latexmk -pdf -interaction=nonstopmode -file-line-error -outdir={outdir} {main}",
+    ),
+    (
+        "pdflatex",
+        "pdflatex -interaction=nonstopmode -file-line-error -output-directory={outdir} {main}",
+    ),
+    (
+        "lualatex",
+        "lualatex -interaction=nonstopmode -file-line-error -output-directory={outdir} {main}",
+    ),
+    (
+        "xelatex",
+        "xelatex -interaction=nonstopmode -file-line-error -output-directory={outdir} {main}",
+    ),
+    ("tectonic", "tectonic --keep-logs --outdir {outdir} {main}"),
+];
+
+/// Resolves the configured engine into a program and its arguments.
+/// Custom commands which do not use `{main}` receive the main file as their last argument
+pub fn command(engine: &str, main: &str, output_dir: &str) -> Result<Vec<String>, Error> {
+    let template = PRESETS
+        .iter()
+        .find(|(name, _)| *name == engine)
+        .map(|(_, command)| *command)
+        .unwrap_or(engine);
+    let mut args = split(template)?;
+    if args.is_empty() {
+        return Err(Error::InvalidEngine {
+            engine: engine.to_string(),
+            details: "the command is empty".to_string(),
+        });
+    }
+    if !args.iter().any(|arg| arg.contains("{main}")) {
+        args.push("{main}".to_string());
+    }
+    Ok(args
+        .into_iter()
+        .map(|arg| arg.replace("{main}", main).replace("{outdir}", output_dir))
+        .collect())
+}
+
+/// Splits a command into its arguments, respecting single and double quotes
+fn split(command: &str) -> Result<Vec<String>, Error> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for char in command.chars() {
+        match (quote, char) {
+            (Some(open), char) if char == open => quote = None,
+            (Some(_), char) => current.get_or_insert_with(String::new).push(char),
+            (None, '"' | '\'') => {
+                quote = Some(char);
+                current.get_or_insert_with(String::new);
+            }
+            (None, char) if char.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            (None, char) => current.get_or_insert_with(String::new).push(char),
+        }
+    }
+    if quote.is_some() {
+        return Err(Error::InvalidEngine {
+            engine: command.to_string(),
+            details: "the command contains an unterminated quote".to_string(),
+        });
+    }
+    args.extend(current);
+    Ok(args)
+}
@@ -0,0 +1,72 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The commands whose argument is another source file of the document
+const INPUT_COMMANDS: [&str; 3] = ["\\input{", "\\include{", "\\subfile{"];
+
+/// Follows the `\input` graph starting at the main file.
+/// Returns all source files of the document which exist, relative to the project root.
+/// Files inside `excluded_dir` (e.g. the build output) are never part of the graph
+pub fn input_graph(project_path: &Path, main: &str, excluded_dir: &Path) -> BTreeSet<PathBuf> {
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![PathBuf::from(main)];
+    while let Some(file) = pending.pop() {
+        if file.starts_with(excluded_dir) || visited.contains(&file) {
+            continue;
+        }
+        let contents = match fs::read_to_string(project_path.join(&file)) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+        visited.insert(file);
+        for input in inputs(&contents) {
+            // Like LaTeX, try the name as-is and with the `.tex` extension
+            let with_extension = PathBuf::from(format!("{input}.tex"));
+            let input = PathBuf::from(input);
+            if project_path.join(&input).is_file() {
+                pending.push(input);
+            } else if project_path.join(&with_extension).is_file() {
+                pending.push(with_extension);
+            }
+        }
+    }
+    visited
+}
+
+/// Extracts the arguments of all input commands outside of comments
+fn inputs(contents: &str) -> Vec<String> {
+    let mut inputs = vec![];
+    for line in contents.lines() {
+        let line = strip_comment(line);
+        for command in INPUT_COMMANDS {
+            let mut rest = line;
+            while let Some(position) = rest.find(command) {
+                rest = &rest[position + command.len()..];
+                if let Some(end) = rest.find('}') {
+                    let argument = rest[..end].trim();
+                    if !argument.is_empty() {
+                        inputs.push(argument.to_string());
+                    }
+                    rest = &rest[end..];
+                }
+            }
+        }
+    }
+    inputs
+}
+
+/// Removes a trailing comment (an unescaped `%`) from a line
+fn strip_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (index, char) in line.char_indices() {
+        match char {
+            '\\' => escaped = !escaped,
+            '%' if !escaped => return &line[..index],
+            _ => escaped = false,
+        }
+    }
+    line
+}
@@ -0,0 +1,193 @@
+mod diagnostics;
+mod engine;
+mod inputs;
+mod watch;
+
+pub use diagnostics::{parse_log, Diagnostic, Severity};
+pub use watch::watch;
+
+use std::{
+    error,
+    fmt::Display,
+    fs, io,
+    path::Path,
+    process::{Command, ExitStatus},
+};
+
+use log::{debug, error, info, warn};
+
+use crate::{
+    config::BuildConfig,
+    error::{Category, Diagnose},
+    project::Provenance,
+    templates::Manifest,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidEngine {
+        engine: String,
+        details: String,
+    },
+    EngineNotFound(String),
+    IO(io::Error),
+    Failed {
+        status: ExitStatus,
+        diagnostics: Vec<Diagnostic>,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::InvalidEngine { engine, details } =>
+                    format!("Invalid engine command `{engine}`: {details}"),
+                Self::EngineNotFound(program) => format!("Could not find engine `{program}`"),
+                Self::IO(_) => "IO error".to_string(),
+                Self::Failed {
+                    status,
+                    diagnostics,
+                } => format!(
+                    "LaTeX engine failed ({status}) with {} error(s)",
+                    diagnostics
+                        .iter()
+                        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                        .count()
+                ),
+            }
+        )
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnose for Error {
+    fn category(&self) -> Category {
+        match self {
+            Self::InvalidEngine { .. } => Category::Config,
+            Self::EngineNotFound(_) | Self::Failed { .. } => Category::Build,
+            Self::IO(_) => Category::IO,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            Self::EngineNotFound(_) => Some(
+                "check that it is installed and in your $PATH or configure another engine"
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+/// Runs the configured LaTeX engine on the main file of the project at `project_path`.
+/// Returns the errors and warnings found in the engine's log file
+pub fn build(config: &BuildConfig, project_path: &Path) -> Result<Vec<Diagnostic>, Error> {
+    let main = main_file(project_path);
+    let args = engine::command(&config.engine, &main, &config.output_dir)?;
+    fs::create_dir_all(project_path.join(&config.output_dir))?;
+    debug!("Running `{}`...", args.join(" "));
+    let output = match Command::new(&args[0])
+        .args(&args[1..])
+        .current_dir(project_path)
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(Error::EngineNotFound(args[0].clone()))
+        }
+        Err(err) => return Err(Error::IO(err)),
+    };
+    debug!("{}", String::from_utf8_lossy(&output.stdout));
+    debug!("{}", String::from_utf8_lossy(&output.stderr));
+    // Parse the log file which is named after the main file
+    let stem = Path::new(&main)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let log_path = project_path
+        .join(&config.output_dir)
+        .join(format!("{stem}.log"));
+    let diagnostics = match fs::read(&log_path) {
+        Ok(log) => parse_log(&String::from_utf8_lossy(&log)),
+        Err(err) => {
+            warn!(
+                "Could not read log file at `{}`: {err}",
+                log_path.to_string_lossy()
+            );
+            vec![]
+        }
+    };
+    if !output.status.success() {
+        return Err(Error::Failed {
+            status: output.status,
+            diagnostics,
+        });
+    }
+    Ok(diagnostics)
+}
+
+/// Logs the given errors and warnings
+pub fn log_diagnostics(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        match diagnostic.severity {
+            Severity::Error => error!("{diagnostic}"),
+            Severity::Warning => warn!("{diagnostic}"),
+        }
+    }
+}
+
+/// Logs a summary after a successful build
+pub fn log_success(config: &BuildConfig, project_path: &Path, diagnostics: &[Diagnostic]) {
+    info!(
+        "Built project into `{}` ({} warning(s))",
+        project_path.join(&config.output_dir).to_string_lossy(),
+        warning_count(diagnostics)
+    );
+}
+
+/// Counts the warnings among the given diagnostics
+fn warning_count(diagnostics: &[Diagnostic]) -> usize {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+        .count()
+}
+
+/// Determines the project's main file using its provenance
+fn main_file(project_path: &Path) -> String {
+    match Provenance::read(project_path) {
+        Ok(provenance) if Path::new(&provenance.template.main).file_stem().is_some() => {
+            provenance.template.main
+        }
+        Ok(provenance) => {
+            let main = Manifest::default().main;
+            warn!(
+                "Invalid main file `{}` in project provenance, using `{main}` instead",
+                provenance.template.main
+            );
+            main
+        }
+        Err(err) => {
+            debug!("Could not read project provenance, assuming default main file: {err}");
+            Manifest::default().main
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use log::{error, info, warn};
+
+use crate::config::BuildConfig;
+
+use super::{build, inputs::input_graph, main_file, warning_count, Diagnostic, Error, Severity};
+
+/// How often the source files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The modification times of the watched files (`None` if a file vanished)
+type Snapshot = BTreeMap<PathBuf, Option<SystemTime>>;
+
+/// Rebuilds the project whenever a file of its `\input` graph changes.
+/// A rebuild starts once no further changes occurred for the debounce duration.
+/// Only returns if the engine cannot be run at all
+pub fn watch(config: &BuildConfig, project_path: &Path, debounce: Duration) -> Result<(), Error> {
+    let main = main_file(project_path);
+    let output_dir = Path::new(&config.output_dir);
+    let mut previous_errors = vec![];
+    loop {
+        // Build and report the outcome
+        info!("Building...");
+        let errors = match build(config, project_path) {
+            Ok(diagnostics) => {
+                info!(
+                    "Build succeeded ({} warning(s))",
+                    warning_count(&diagnostics)
+                );
+                vec![]
+            }
+            Err(Error::Failed { diagnostics, .. }) => {
+                let errors: Vec<Diagnostic> = diagnostics
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                    .collect();
+                let new: Vec<&Diagnostic> = errors
+                    .iter()
+                    .filter(|error| !previous_errors.contains(*error))
+                    .collect();
+                error!(
+                    "Build failed with {} error(s), {} new:",
+                    errors.len(),
+                    new.len()
+                );
+                for diagnostic in new {
+                    error!("  {diagnostic}");
+                }
+                errors
+            }
+            Err(err) => return Err(err),
+        };
+        previous_errors = errors;
+        // Wait for changes of the sources (which may have changed during the build)
+        let files = input_graph(project_path, &main, output_dir);
+        if files.is_empty() {
+            warn!("Main file `{main}` does not exist: only watching for its creation");
+        }
+        info!("Watching {} file(s) for changes...", files.len());
+        let mut snapshot = snapshot(project_path, files.iter().chain([&PathBuf::from(&main)]));
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current = snapshot_of(project_path, &snapshot);
+            if current != snapshot {
+                snapshot = current;
+                break;
+            }
+        }
+        // Debounce: wait until the files stop changing
+        loop {
+            thread::sleep(debounce);
+            let current = snapshot_of(project_path, &snapshot);
+            if current == snapshot {
+                break;
+            }
+            snapshot = current;
+        }
+    }
+}
+
+/// Records the modification times of the given files
+fn snapshot<'f>(project_path: &Path, files: impl Iterator<Item = &'f PathBuf>) -> Snapshot {
+    files
+        .map(|file| {
+            (
+                file.clone(),
+                fs::metadata(project_path.join(file))
+                    .and_then(|metadata| metadata.modified())
+                    .ok(),
+            )
+        })
+        .collect()
+}
+
+/// Records the modification times of the files of a previous snapshot
+fn snapshot_of(project_path: &Path, previous: &Snapshot) -> Snapshot {
+    snapshot(project_path, previous.keys())
+}
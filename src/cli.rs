@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use vitex::config::{Author, Template};
+
+use crate::{output::OutputFormat, project, wizard};
 
 #[derive(Parser)]
 #[clap(author, version, about)]
@@ -9,6 +12,12 @@ pub struct Args {
     /// If set, more information will be printed to the console
     #[clap(short, long, value_parser, global = true)]
     pub verbose: bool,
+    /// The profile which provides the author and defaults of new projects (overrides `default_profile`)
+    #[clap(long, value_parser, global = true, env = "VITEX_PROFILE")]
+    pub profile: Option<String>,
+    /// How results are printed (`json` prints results to stdout and logs to stderr)
+    #[clap(long, value_enum, global = true, default_value = "text")]
+    pub output: OutputFormat,
     /// The subcommand to execute
     #[clap(subcommand)]
     pub command: Command,
@@ -16,8 +25,13 @@ pub struct Args {
 
 #[derive(Subcommand)]
 pub enum Command {
-    /// Outputs the location of the program's configuration file
-    Config,
+    /// Sets up the configuration by asking a few questions (the flags allow unattended setups)
+    Init(InitArgs),
+    /// Configuration subcommands (outputs the location of the configuration file by default)
+    Config {
+        #[clap(subcommand)]
+        command: Option<ConfigCommand>,
+    },
     /// Templates subcommands
     #[clap(subcommand)]
     Templates(TemplateCommand),
@@ -26,6 +40,59 @@ pub enum Command {
     Project(ProjectCommand),
 }
 
+#[derive(clap::Args, Clone, Default)]
+pub struct InitArgs {
+    /// Do not ask any questions (also applies if the input is not a terminal)
+    #[clap(short, long, value_parser)]
+    pub yes: bool,
+    /// Overwrite an existing configuration (a backup is kept)
+    #[clap(long, value_parser)]
+    pub force: bool,
+    /// The default author, e.g. `Alice <alice@example.com>` (repeatable, detected by default)
+    #[clap(short, long, value_parser)]
+    pub author: Vec<Author>,
+    /// A built-in template to enable (repeatable, all are enabled by default)
+    #[clap(short, long, value_parser)]
+    pub template: Vec<String>,
+    /// Do not enable any built-in templates
+    #[clap(long, value_parser, conflicts_with = "template")]
+    pub no_templates: bool,
+    /// A template from a git repository as `ID=REPOSITORY[#PATH_PREFIX]` (repeatable)
+    #[clap(long, value_parser = wizard::parse_git_template)]
+    pub git_template: Vec<Template>,
+    /// Clone the git templates after writing the configuration
+    #[clap(long, value_parser)]
+    pub sync: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Outputs the value of a setting
+    Get {
+        /// The setting's key (nested keys are separated by dots, e.g. `build.engine`)
+        key: String,
+    },
+    /// Changes a setting whilst preserving the file's comments and formatting
+    Set {
+        /// The setting's key (nested keys are separated by dots, e.g. `build.engine`)
+        key: String,
+        /// The new value (non-string settings are parsed as TOML, e.g. `true` or `["aux"]`)
+        value: String,
+    },
+    /// Validates the configuration file without changing anything
+    Validate,
+    /// Opens the configuration file in `$EDITOR` and validates it afterwards
+    Edit,
+    /// Outputs the effective configuration including default values
+    Show,
+    /// Outputs the JSON Schema of the configuration file (e.g. for TOML language servers)
+    Schema {
+        /// Output the schema of template manifests (`vitex.toml`) instead
+        #[clap(long, value_parser)]
+        manifest: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum TemplateCommand {
     /// Downloads and syncs the specified templates
@@ -36,6 +103,36 @@ pub enum TemplateCommand {
     List,
     /// Purges all cloned templates (does not affect local templates)
     Purge,
+    /// Registers a new template in the configuration
+    Add {
+        /// The template's unique ID
+        id: String,
+        /// The git repository which contains the template
+        #[clap(
+            long,
+            value_parser,
+            conflicts_with = "local",
+            required_unless_present = "local"
+        )]
+        git: Option<String>,
+        /// The template's directory inside the repository
+        #[clap(long, value_parser, requires = "git", default_value = "")]
+        path_prefix: String,
+        /// A branch, tag or commit to use instead of the default branch
+        #[clap(long, value_parser, requires = "git")]
+        rev: Option<String>,
+        /// A local directory which is copied into the custom templates
+        #[clap(long, value_parser)]
+        local: Option<String>,
+        /// Clone the template right away
+        #[clap(long, value_parser, requires = "git")]
+        sync: bool,
+    },
+    /// Removes a template from the configuration and deletes its clone
+    Remove {
+        /// The ID of the template to remove
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -50,8 +147,134 @@ pub enum ProjectCommand {
         /// The project's template
         #[clap(short, long, value_parser)]
         template: Option<String>,
-        /// The project's author(s)
+        /// The project's author, e.g. `Alice <alice@example.com>; student_id=1234` (repeat for multiple authors)
         #[clap(short, long, value_parser)]
-        author: Option<String>,
+        author: Vec<Author>,
+        /// A TOML file which lists the project's `[[authors]]`
+        #[clap(long, value_parser, conflicts_with = "author")]
+        authors_file: Option<String>,
+        /// Initialize a git repository with an initial commit (overrides the config)
+        #[clap(long, value_parser)]
+        git: bool,
+        /// Do not initialize a git repository (overrides the config)
+        #[clap(long, value_parser, conflicts_with = "git")]
+        no_git: bool,
+        /// Hardlink the template's immutable assets instead of copying them (overrides the config)
+        #[clap(long, value_parser)]
+        hardlink_assets: bool,
     },
+    /// Applies a template to an existing directory
+    Init {
+        /// The project's title
+        title: String,
+        /// The directory to apply the template to (defaults to the current directory)
+        #[clap(short, long, value_parser, default_value = ".")]
+        directory: String,
+        /// How existing files which are also part of the template are handled
+        #[clap(long, value_enum, default_value = "abort")]
+        on_conflict: ConflictPolicy,
+        /// The project's subtitle
+        #[clap(short, long, value_parser)]
+        subtitle: Option<String>,
+        /// The project's template
+        #[clap(short, long, value_parser)]
+        template: Option<String>,
+        /// The project's author, e.g. `Alice <alice@example.com>; student_id=1234` (repeat for multiple authors)
+        #[clap(short, long, value_parser)]
+        author: Vec<Author>,
+        /// A TOML file which lists the project's `[[authors]]`
+        #[clap(long, value_parser, conflicts_with = "author")]
+        authors_file: Option<String>,
+        /// Initialize a git repository with an initial commit (overrides the config)
+        #[clap(long, value_parser)]
+        git: bool,
+        /// Do not initialize a git repository (overrides the config)
+        #[clap(long, value_parser, conflicts_with = "git")]
+        no_git: bool,
+        /// Hardlink the template's immutable assets instead of copying them (overrides the config)
+        #[clap(long, value_parser)]
+        hardlink_assets: bool,
+    },
+    /// Merges changes of the template's current revision into a project
+    Update {
+        /// The project's directory (defaults to the current directory)
+        #[clap(short, long, value_parser, default_value = ".")]
+        directory: String,
+    },
+    /// Builds a project using the configured LaTeX engine
+    Build {
+        /// The project's directory (defaults to the current directory)
+        #[clap(short, long, value_parser, default_value = ".")]
+        directory: String,
+        /// The engine preset or custom command to use (overrides the config)
+        #[clap(short, long, value_parser)]
+        engine: Option<String>,
+        /// The directory which holds the build output (overrides the config)
+        #[clap(short, long, value_parser)]
+        output_dir: Option<String>,
+    },
+    /// Rebuilds a project whenever one of its source files changes
+    Watch {
+        /// The project's directory (defaults to the current directory)
+        #[clap(short, long, value_parser, default_value = ".")]
+        directory: String,
+        /// The engine preset or custom command to use (overrides the config)
+        #[clap(short, long, value_parser)]
+        engine: Option<String>,
+        /// The directory which holds the build output (overrides the config)
+        #[clap(short, long, value_parser)]
+        output_dir: Option<String>,
+        /// How long to wait for further changes before rebuilding (in milliseconds)
+        #[clap(long, value_parser, default_value_t = 500)]
+        debounce: u64,
+    },
+    /// Removes LaTeX auxiliary files from a project
+    Clean {
+        /// The project's directory (defaults to the current directory)
+        #[clap(short, long, value_parser, default_value = ".")]
+        directory: String,
+        /// Only print the files which would be removed
+        #[clap(long, value_parser)]
+        dry_run: bool,
+        /// Also remove generated PDFs
+        #[clap(short, long, value_parser)]
+        all: bool,
+    },
+    /// Changes the title, subtitle, author or another value of a project
+    Set {
+        /// The value to change (`title`, `subtitle`, `author` or another `\document<key>` macro)
+        key: String,
+        /// The new value
+        value: String,
+        /// The project's directory (defaults to the current directory)
+        #[clap(short, long, value_parser, default_value = ".")]
+        directory: String,
+        /// Rename the project directory to match a new title
+        #[clap(short, long, value_parser)]
+        rename: bool,
+    },
+}
+
+/// How files of the template which already exist in the destination are handled
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ConflictPolicy {
+    /// Do not apply the template if any file would be overwritten
+    Abort,
+    /// Keep the existing files
+    Skip,
+    /// Replace the existing files with the template's version
+    Overwrite,
+    /// Write the template's version next to the existing file using a `.new` suffix
+    New,
+}
+
+impl From<ConflictPolicy> for project::ConflictPolicy {
+    fn from(policy: ConflictPolicy) -> Self {
+        match policy {
+            ConflictPolicy::Abort => Self::Abort,
+            ConflictPolicy::Skip => Self::Skip,
+            ConflictPolicy::Overwrite => Self::Overwrite,
+            ConflictPolicy::New => Self::New,
+        }
+    }
 }
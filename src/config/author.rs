@@ -0,0 +1,109 @@
+use std::{fmt::Display, fs, path::Path, str::FromStr};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{errors::Result, locate};
+
+/// An author of a project
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Author {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affiliation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub student_id: Option<String>,
+}
+
+/// A file which lists the authors of a project (e.g. the members of a group)
+#[derive(Deserialize)]
+struct AuthorsFile {
+    authors: Vec<Author>,
+}
+
+impl Author {
+    /// The names of all fields which can be used in templates
+    pub const FIELDS: [&'static str; 4] = ["name", "email", "affiliation", "student_id"];
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the value of a field by its name (missing fields are empty)
+    pub fn field(&self, field: &str) -> Option<&str> {
+        match field {
+            "name" => Some(&self.name),
+            "email" => self.email.as_deref(),
+            "affiliation" => self.affiliation.as_deref(),
+            "student_id" => self.student_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Parses `Name <email>` optionally followed by `; key=value` pairs,
+/// e.g. `Alice <alice@example.com>; student_id=1234`
+impl FromStr for Author {
+    type Err = String;
+
+    fn from_str(raw: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = raw.split(';');
+        let identity = parts.next().unwrap_or_default().trim();
+        let mut author = match identity.split_once('<') {
+            Some((name, email)) => Self {
+                email: Some(
+                    email
+                        .strip_suffix('>')
+                        .ok_or("the email address must be enclosed in `<...>`")?
+                        .trim()
+                        .to_string(),
+                ),
+                ..Self::new(name.trim())
+            },
+            None => Self::new(identity),
+        };
+        if author.name.is_empty() {
+            return Err("the author's name must not be empty".to_string());
+        }
+        for pair in parts.map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("`{pair}` is not a `key=value` pair"))?;
+            let value = Some(value.trim().to_string());
+            match key.trim() {
+                "email" => author.email = value,
+                "affiliation" => author.affiliation = value,
+                "student_id" => author.student_id = value,
+                key => {
+                    return Err(format!(
+                        "unknown field `{key}` (expected `email`, `affiliation` or `student_id`)"
+                    ))
+                }
+            }
+        }
+        Ok(author)
+    }
+}
+
+impl Display for Author {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.email {
+            Some(email) => write!(f, "{} <{email}>", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Reads the `[[authors]]` of a TOML file
+pub fn read_authors(path: &Path) -> Result<Vec<Author>> {
+    let contents = fs::read_to_string(path)?;
+    let file: AuthorsFile =
+        toml::from_str(&contents).map_err(|err| locate::decode_error(path, &contents, err))?;
+    Ok(file.authors)
+}
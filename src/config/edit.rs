@@ -0,0 +1,238 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table, TableLike, Value};
+
+use super::{
+    errors::{Error, Result},
+    Config, Template,
+};
+
+/// The configuration file as written by the user.
+/// Edits preserve the comments and formatting of the file
+pub struct ConfigDocument {
+    path: PathBuf,
+    document: DocumentMut,
+}
+
+impl ConfigDocument {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            document: fs::read_to_string(path)?.parse()?,
+        })
+    }
+
+    /// Decodes the (possibly edited) configuration without validating it
+    pub fn config(&self) -> Result<Config> {
+        Ok(toml::from_str(&self.document.to_string())?)
+    }
+
+    /// Appends a template to the `templates` array
+    pub fn add_template(&mut self, template: &Template) -> Result<()> {
+        let mut git = Table::new();
+        git["repository"] = value(&template.git.repository);
+        git["path_prefix"] = value(&template.git.path_prefix);
+        if let Some(rev) = &template.git.rev {
+            git["rev"] = value(rev);
+        }
+        let mut entry = Table::new();
+        entry["id"] = value(&template.id);
+        entry["git"] = Item::Table(git);
+
+        // An empty inline array is replaced so that the new entry is readable
+        if self
+            .document
+            .get("templates")
+            .and_then(Item::as_array)
+            .is_some_and(|array| array.is_empty())
+        {
+            self.document.remove("templates");
+        }
+        match self
+            .document
+            .entry("templates")
+            .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        {
+            Item::ArrayOfTables(templates) => templates.push(entry),
+            Item::Value(Value::Array(templates)) => {
+                templates.push(entry.into_inline_table());
+            }
+            _ => return Err(Error::Malformed("`templates` is not an array".to_string())),
+        }
+        Ok(())
+    }
+
+    /// Removes the template with the given ID from the `templates` array
+    pub fn remove_template(&mut self, id: &str) -> Result<()> {
+        let has_id = |entry: Option<&Value>| entry.and_then(Value::as_str) == Some(id);
+        match self.document.get_mut("templates") {
+            Some(Item::ArrayOfTables(templates)) => {
+                let index = templates
+                    .iter()
+                    .position(|table| has_id(table.get("id").and_then(Item::as_value)));
+                if let Some(index) = index {
+                    templates.remove(index);
+                    return Ok(());
+                }
+            }
+            Some(Item::Value(Value::Array(templates))) => {
+                let index = templates.iter().position(|entry| {
+                    has_id(entry.as_inline_table().and_then(|table| table.get("id")))
+                });
+                if let Some(index) = index {
+                    templates.remove(index);
+                    return Ok(());
+                }
+            }
+            Some(_) => return Err(Error::Malformed("`templates` is not an array".to_string())),
+            None => {}
+        }
+        Err(Error::UnknownTemplate(id.to_string()))
+    }
+
+    /// Sets the setting at a dotted key (e.g. `build.engine`).
+    /// Strings are taken literally whilst other values are parsed as TOML (e.g. `true` or `["aux"]`)
+    pub fn set(&mut self, key: &str, raw_value: &str) -> Result<()> {
+        let invalid = |reason: &str| Error::InvalidValue {
+            key: key.to_string(),
+            reason: reason.to_string(),
+        };
+        if key == "templates" || key.starts_with("templates.") {
+            return Err(invalid(
+                "use `vitex templates add` or `vitex templates remove` instead",
+            ));
+        }
+        // The type of the current (or default) value determines how the new value is parsed.
+        // Settings without a value (e.g. new variables or unset optional settings) use the schema
+        let (is_string, is_section) = match self.config()?.get(key) {
+            Ok(current) => (current.is_str(), current.is_table()),
+            Err(Error::UnknownKey(_)) => {
+                let schema =
+                    setting_schema(key).ok_or_else(|| Error::UnknownKey(key.to_string()))?;
+                (
+                    has_type(&schema, InstanceType::String),
+                    has_type(&schema, InstanceType::Object),
+                )
+            }
+            Err(err) => return Err(err),
+        };
+        let new_value = match (is_string, is_section) {
+            (true, _) => Value::from(raw_value),
+            (_, true) => return Err(invalid("it is a section: set its keys instead")),
+            _ => raw_value
+                .parse::<Value>()
+                .map_err(|_| invalid(&format!("`{raw_value}` is not a valid TOML value")))?,
+        };
+        let mut segments: Vec<&str> = key.split('.').collect();
+        let last = segments
+            .pop()
+            .expect("A key always has at least one segment");
+        let mut table: &mut dyn TableLike = self.document.as_table_mut();
+        for segment in segments {
+            table = table
+                .entry(segment)
+                .or_insert(Item::Table(Table::new()))
+                .as_table_like_mut()
+                .ok_or_else(|| Error::Malformed(format!("`{segment}` is not a section")))?;
+        }
+        // Keep comments which are attached to the previous value
+        match table.get_mut(last).and_then(Item::as_value_mut) {
+            Some(existing) => {
+                let decor = existing.decor().clone();
+                *existing = new_value;
+                *existing.decor_mut() = decor;
+            }
+            None => {
+                table.insert(last, Item::Value(new_value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the document back to the configuration file
+    pub fn save(&self) -> Result<()> {
+        fs::write(&self.path, self.document.to_string())?;
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Returns the setting at a dotted key (e.g. `build.engine`)
+    pub fn get(&self, key: &str) -> Result<toml::Value> {
+        let mut current =
+            toml::Value::try_from(self).expect("The config struct must always be encodable");
+        for segment in key.split('.') {
+            current = match current {
+                toml::Value::Table(mut table) => table.remove(segment),
+                _ => None,
+            }
+            .ok_or_else(|| Error::UnknownKey(key.to_string()))?;
+        }
+        Ok(current)
+    }
+}
+
+/// Looks up the schema of the setting at a dotted key.
+/// Keys of map-typed sections (e.g. `variables` or `profiles`) may be chosen freely
+fn setting_schema(key: &str) -> Option<SchemaObject> {
+    let root = Config::schema();
+    // Settings of struct types refer to their definition (wrapped in `allOf` if documented)
+    let resolve = |schema: &Schema| {
+        let Schema::Object(object) = schema else {
+            return None;
+        };
+        let reference = object.reference.as_deref().or_else(|| {
+            match object.subschemas.as_ref()?.all_of.as_deref()? {
+                [Schema::Object(inner)] => inner.reference.as_deref(),
+                _ => None,
+            }
+        });
+        match reference {
+            Some(reference) => match root
+                .definitions
+                .get(reference.strip_prefix("#/definitions/")?)?
+            {
+                Schema::Object(definition) => Some(definition.clone()),
+                Schema::Bool(_) => None,
+            },
+            None => Some(object.clone()),
+        }
+    };
+    let mut current = root.schema.clone();
+    for segment in key.split('.') {
+        let object = current.object.as_ref()?;
+        let next = object
+            .properties
+            .get(segment)
+            .or(object.additional_properties.as_deref())?;
+        current = resolve(next)?;
+    }
+    Some(current)
+}
+
+/// Whether a schema allows values of the given type (e.g. `Option<String>` allows strings)
+fn has_type(schema: &SchemaObject, instance_type: InstanceType) -> bool {
+    match &schema.instance_type {
+        Some(SingleOrVec::Single(single)) => **single == instance_type,
+        Some(SingleOrVec::Vec(types)) => types.contains(&instance_type),
+        None => false,
+    }
+}
+
+/// Changes a single setting of the configuration file and validates the result
+pub fn set_value(
+    config_path: &Path,
+    custom_templates_path: &Path,
+    key: &str,
+    raw_value: &str,
+) -> Result<Config> {
+    let mut document = ConfigDocument::open(config_path)?;
+    document.set(key, raw_value)?;
+    let config = document.config()?.validate(custom_templates_path)?;
+    document.save()?;
+    Ok(config)
+}
@@ -1,13 +1,43 @@
-use std::{fmt::Display, io};
+use std::{error, fmt::Display, io};
+
+use crate::{
+    error::{Category, Diagnose, Location},
+    project::CopyError,
+};
 
 use super::validate::ValidateError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[derive(Debug)]
 pub enum Error {
     IO(io::Error),
-    TomlDecode(toml::de::Error),
-    Validate(ValidateError),
+    TomlDecode {
+        error: toml::de::Error,
+        location: Option<Box<Location>>,
+    },
+    TomlEdit {
+        error: toml_edit::TomlError,
+        location: Option<Box<Location>>,
+    },
+    Validate {
+        error: ValidateError,
+        locations: Vec<Location>,
+    },
+    UnknownField {
+        key: String,
+        suggestion: Option<String>,
+        location: Option<Box<Location>>,
+    },
+    Malformed(String),
+    UnknownTemplate(String),
+    UnknownKey(String),
+    UnknownProfile(String),
+    InvalidValue {
+        key: String,
+        reason: String,
+    },
+    Copy(CopyError),
 }
 
 impl Display for Error {
@@ -16,14 +46,116 @@ impl Display for Error {
             f,
             "{}",
             match self {
-                Self::TomlDecode(err) => format!("could not decode TOML syntax: {err}"),
-                Self::IO(err) => format!("could not perform IO operation: {err}"),
-                Self::Validate(err) => format!("configuration invalid: {err}"),
+                // The location already shows where the error is
+                Self::TomlEdit {
+                    error,
+                    location: Some(_),
+                } => format!("could not decode TOML syntax: {}", error.message().trim()),
+                Self::TomlDecode { .. } | Self::TomlEdit { .. } =>
+                    "could not decode TOML syntax".to_string(),
+                Self::IO(_) => "could not perform IO operation".to_string(),
+                Self::Validate { .. } => "configuration invalid".to_string(),
+                Self::UnknownField { key, .. } => format!("unknown setting `{key}`"),
+                Self::Malformed(reason) => format!("configuration file is malformed: {reason}"),
+                Self::UnknownTemplate(id) => format!("template `{id}` is not configured"),
+                Self::UnknownKey(key) => format!("setting `{key}` does not exist"),
+                Self::UnknownProfile(name) => format!("profile `{name}` is not configured"),
+                Self::InvalidValue { key, reason } =>
+                    format!("invalid value for setting `{key}`: {reason}"),
+                Self::Copy(_) => "could not copy the template".to_string(),
             }
         )
     }
 }
 
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            Self::TomlDecode { error, .. } => Some(error),
+            Self::TomlEdit {
+                location: Some(_), ..
+            } => None,
+            Self::TomlEdit { error, .. } => Some(error),
+            Self::Validate { error, .. } => Some(error),
+            Self::Copy(err) => Some(err),
+            Self::UnknownField { .. }
+            | Self::Malformed(_)
+            | Self::UnknownTemplate(_)
+            | Self::UnknownKey(_)
+            | Self::UnknownProfile(_)
+            | Self::InvalidValue { .. } => None,
+        }
+    }
+}
+
+impl Diagnose for Error {
+    fn category(&self) -> Category {
+        match self {
+            Self::IO(_) => Category::IO,
+            Self::Copy(err) => err.category(),
+            Self::TomlDecode { .. }
+            | Self::TomlEdit { .. }
+            | Self::Validate { .. }
+            | Self::UnknownField { .. }
+            | Self::Malformed(_) => Category::Config,
+            Self::UnknownTemplate(_)
+            | Self::UnknownKey(_)
+            | Self::UnknownProfile(_)
+            | Self::InvalidValue { .. } => Category::UserInput,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            Self::Validate { error, .. } => error.hint(),
+            Self::Copy(err) => err.hint(),
+            Self::UnknownField {
+                suggestion: Some(suggestion),
+                ..
+            } => Some(format!("did you mean `{suggestion}`?")),
+            Self::TomlDecode { .. }
+            | Self::TomlEdit { .. }
+            | Self::UnknownField { .. }
+            | Self::Malformed(_) => Some(
+                "fix the file's syntax (use `vitex config edit` for the configuration file)"
+                    .to_string(),
+            ),
+            Self::UnknownTemplate(_) => {
+                Some("run `vitex templates list` to see the configured templates".to_string())
+            }
+            Self::UnknownKey(_) => {
+                Some("run `vitex config show` to see the available settings".to_string())
+            }
+            Self::UnknownProfile(_) => {
+                Some("run `vitex config get profiles` to see the configured profiles".to_string())
+            }
+            Self::IO(_) | Self::InvalidValue { .. } => None,
+        }
+    }
+
+    fn locations(&self) -> Vec<Location> {
+        match self {
+            Self::TomlDecode { location, .. }
+            | Self::TomlEdit { location, .. }
+            | Self::UnknownField { location, .. } => {
+                location.iter().map(|location| *location.clone()).collect()
+            }
+            Self::Validate { locations, .. } => locations.clone(),
+            _ => vec![],
+        }
+    }
+}
+
+impl From<CopyError> for Error {
+    fn from(err: CopyError) -> Self {
+        match err {
+            CopyError::IO(err) => Self::IO(err),
+            err => Self::Copy(err),
+        }
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Self {
         Self::IO(err)
@@ -32,12 +164,27 @@ impl From<io::Error> for Error {
 
 impl From<toml::de::Error> for Error {
     fn from(err: toml::de::Error) -> Self {
-        Self::TomlDecode(err)
+        Self::TomlDecode {
+            error: err,
+            location: None,
+        }
+    }
+}
+
+impl From<toml_edit::TomlError> for Error {
+    fn from(err: toml_edit::TomlError) -> Self {
+        Self::TomlEdit {
+            error: err,
+            location: None,
+        }
     }
 }
 
 impl From<ValidateError> for Error {
     fn from(err: ValidateError) -> Self {
-        Self::Validate(err)
+        Self::Validate {
+            error: err,
+            locations: vec![],
+        }
     }
 }
@@ -0,0 +1,62 @@
+use std::{env, fs, process::Command};
+
+use log::{debug, warn};
+
+use super::Author;
+
+/// The passwd database which holds the user's full name
+const PASSWD_PATH: &str = "/etc/passwd";
+
+/// Detects the user's identity from `$VITEX_AUTHOR`, git's `user.name` and `user.email`
+/// or the GECOS field of the user's passwd entry (in that order)
+pub fn detect_author() -> Option<Author> {
+    let (source, author) = from_env()
+        .map(|author| ("$VITEX_AUTHOR", author))
+        .or_else(|| from_git().map(|author| ("git", author)))
+        .or_else(|| from_passwd().map(|author| ("passwd", author)))?;
+    debug!("Detected author `{author}` using {source}");
+    Some(author)
+}
+
+fn from_env() -> Option<Author> {
+    let raw = env::var("VITEX_AUTHOR").ok()?;
+    match raw.parse() {
+        Ok(author) => Some(author),
+        Err(reason) => {
+            warn!("Ignoring `$VITEX_AUTHOR`: {reason}");
+            None
+        }
+    }
+}
+
+fn from_git() -> Option<Author> {
+    let get = |key: &str| {
+        let output = Command::new("git")
+            .args(["config", "--get", key])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!value.is_empty()).then_some(value)
+    };
+    Some(Author {
+        email: get("user.email"),
+        ..Author::new(&get("user.name")?)
+    })
+}
+
+/// Uses the full name of the GECOS field (`name,room,phone,...`)
+fn from_passwd() -> Option<Author> {
+    let user = env::var("USER").or_else(|_| env::var("LOGNAME")).ok()?;
+    let passwd = fs::read_to_string(PASSWD_PATH).ok()?;
+    let name = passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<&str>>())
+        .find(|fields| fields.first() == Some(&user.as_str()))?
+        .get(4)?
+        .split(',')
+        .next()?
+        .trim()
+        .to_string();
+    (!name.is_empty()).then(|| Author::new(&name))
+}
@@ -0,0 +1,189 @@
+use std::{ops::Range, path::Path};
+
+use serde_ignored::Path as IgnoredPath;
+use toml_edit::{ImDocument, Item, Table, TableLike, TomlError, Value};
+
+use crate::error::Location;
+
+use super::{errors::Error, Config, ValidateError};
+
+/// The minimum similarity of an unknown setting to a known one for it to be suggested
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// Attaches the position of a syntax error
+pub(super) fn syntax_error(origin: &Path, contents: &str, err: TomlError) -> Error {
+    Error::TomlEdit {
+        location: err
+            .span()
+            .map(|span| Box::new(Location::from_span(origin, contents, span, None))),
+        error: err,
+    }
+}
+
+/// Attaches the position of a decoding error (e.g. a value of the wrong type)
+pub(super) fn decode_error(origin: &Path, contents: &str, err: toml::de::Error) -> Error {
+    Error::TomlDecode {
+        location: err.line_col().map(|(line, column)| {
+            Box::new(Location::from_line_col(
+                origin, contents, line, column, None,
+            ))
+        }),
+        error: err,
+    }
+}
+
+/// Reports a setting which is not known to this version of vitex
+pub(super) fn unknown_field(
+    origin: &Path,
+    document: &ImDocument<&str>,
+    config: &Config,
+    path: &[String],
+) -> Error {
+    Error::UnknownField {
+        key: path.join("."),
+        suggestion: suggestion(config, path),
+        location: key_span(document.as_table(), path).map(|span| {
+            Box::new(Location::from_span(
+                origin,
+                document.raw(),
+                span,
+                Some("unknown setting"),
+            ))
+        }),
+    }
+}
+
+/// Points at the `id` of every template which is affected by a validation error
+/// or at the setting which caused it
+pub(super) fn validation_error(
+    origin: &Path,
+    document: &ImDocument<&str>,
+    err: ValidateError,
+) -> Error {
+    let setting = |path: &[&str]| {
+        let path: Vec<String> = path.iter().map(|segment| segment.to_string()).collect();
+        key_span(document.as_table(), &path)
+            .map(|span| Location::from_span(origin, document.raw(), span, None))
+            .into_iter()
+            .collect()
+    };
+    let spans = err
+        .template_id()
+        .map(|id| id_spans(document.as_table(), id))
+        .unwrap_or_default();
+    let locations = match &err {
+        ValidateError::UnknownDefaultProfile(_) => setting(&["default_profile"]),
+        ValidateError::UnknownProfileTemplate { profile, .. } => {
+            setting(&["profiles", profile, "template"])
+        }
+        ValidateError::ProtectedCleanExtension(_) => setting(&["clean", "extensions"]),
+        ValidateError::InvalidVariable { profile: None, key } => setting(&["variables", key]),
+        ValidateError::InvalidVariable {
+            profile: Some(profile),
+            key,
+        } => setting(&["profiles", profile, "variables", key]),
+        ValidateError::DuplicateID(_) => spans
+            .into_iter()
+            .enumerate()
+            .map(|(index, span)| {
+                let label = match index {
+                    0 => "first used here",
+                    _ => "used again here",
+                };
+                Location::from_span(origin, document.raw(), span, Some(label))
+            })
+            .collect(),
+        _ => spans
+            .into_iter()
+            .take(1)
+            .map(|span| Location::from_span(origin, document.raw(), span, None))
+            .collect(),
+    };
+    Error::Validate {
+        error: err,
+        locations,
+    }
+}
+
+/// Converts the path of an ignored setting into its keys (and array indices)
+pub(super) fn segments(path: &IgnoredPath) -> Vec<String> {
+    match path {
+        IgnoredPath::Root => vec![],
+        IgnoredPath::Seq { parent, index } => {
+            let mut segments = segments(parent);
+            segments.push(index.to_string());
+            segments
+        }
+        IgnoredPath::Map { parent, key } => {
+            let mut segments = segments(parent);
+            segments.push(key.clone());
+            segments
+        }
+        IgnoredPath::Some { parent }
+        | IgnoredPath::NewtypeStruct { parent }
+        | IgnoredPath::NewtypeVariant { parent } => segments(parent),
+    }
+}
+
+/// Finds the most similar setting which is known in the same section
+fn suggestion(config: &Config, path: &[String]) -> Option<String> {
+    let (unknown, parents) = path.split_last()?;
+    let mut value = toml::Value::try_from(config).ok()?;
+    for segment in parents {
+        value = match value {
+            toml::Value::Table(mut table) => table.remove(segment)?,
+            toml::Value::Array(array) => array.into_iter().nth(segment.parse().ok()?)?,
+            _ => return None,
+        };
+    }
+    let toml::Value::Table(known) = value else {
+        return None;
+    };
+    known
+        .keys()
+        .map(|key| (strsim::jaro_winkler(unknown, key), key))
+        .filter(|(similarity, _)| *similarity >= SUGGESTION_THRESHOLD)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, key)| key.clone())
+}
+
+/// Returns the span of the key at the given path
+fn key_span(table: &dyn TableLike, path: &[String]) -> Option<Range<usize>> {
+    let (segment, rest) = path.split_first()?;
+    let (key, item) = table.get_key_value(segment)?;
+    if rest.is_empty() {
+        return key.span();
+    }
+    if let Some(table) = item.as_table_like() {
+        return key_span(table, rest);
+    }
+    // Array elements are addressed by their index
+    let (index, rest) = rest.split_first()?;
+    let index: usize = index.parse().ok()?;
+    match item {
+        Item::ArrayOfTables(array) => key_span(array.get(index)?, rest),
+        Item::Value(Value::Array(array)) => key_span(array.get(index)?.as_inline_table()?, rest),
+        _ => None,
+    }
+}
+
+/// Returns the spans of all template IDs which equal `id`
+fn id_spans(root: &Table, id: &str) -> Vec<Range<usize>> {
+    let ids: Vec<Option<Range<usize>>> = match root.get("templates") {
+        Some(Item::ArrayOfTables(templates)) => templates
+            .iter()
+            .filter_map(|template| template.get("id"))
+            .filter(|item| item.as_str() == Some(id))
+            .map(Item::span)
+            .collect(),
+        Some(Item::Value(Value::Array(templates))) => templates
+            .iter()
+            .filter_map(Value::as_inline_table)
+            .filter_map(|template| template.get("id"))
+            .filter(|value| value.as_str() == Some(id))
+            .map(Value::span)
+            .collect(),
+        _ => vec![],
+    };
+    ids.into_iter().flatten().collect()
+}
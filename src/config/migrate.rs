@@ -0,0 +1,225 @@
+use std::{fs, path::Path};
+
+use log::{info, warn};
+use toml_edit::{value, Array, Decor, DocumentMut, InlineTable, Item, TableLike, Value};
+
+use super::{
+    errors::{Error, Result},
+    locate,
+    write::backup,
+};
+
+/// The version of the configuration format which is written by this version of vitex
+pub const CONFIG_VERSION: u32 = 2;
+
+/// The placeholder author name which the default configuration contained before version 2
+const V1_DEFAULT_AUTHOR: &str = "John Doe";
+
+/// Upgrades a document from the version matching its index to the next version
+type Migration = fn(&mut DocumentMut);
+
+/// The migration chain (the entry at index `n` upgrades version `n` to `n + 1`)
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Upgrades the contents of a configuration file to the current version without writing them.
+/// Returns the original version and the upgraded contents if an upgrade is necessary.
+/// Files written by newer versions of vitex are left as they are
+pub fn upgrade(config_path: &Path, contents: &str) -> Result<Option<(u32, String)>> {
+    let mut document: DocumentMut = contents
+        .parse()
+        .map_err(|err| locate::syntax_error(config_path, contents, err))?;
+    let version = match document.get("version") {
+        None => 0,
+        Some(item) => item
+            .as_integer()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                Error::Malformed("`version` must be a non-negative integer".to_string())
+            })?,
+    };
+    if version > CONFIG_VERSION {
+        warn!("Configuration was written by a newer version of vitex (format version {version}, supported: {CONFIG_VERSION})");
+        return Ok(None);
+    }
+    if version == CONFIG_VERSION {
+        return Ok(None);
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut document);
+        document["version"] = value(from as i64 + 1);
+    }
+    Ok(Some((version, document.to_string())))
+}
+
+/// Upgrades the configuration file to the current version and returns its (possibly new) contents.
+/// The original file is backed up next to it before it is overwritten
+pub fn migrate(config_path: &Path, contents: String) -> Result<String> {
+    let (version, migrated) = match upgrade(config_path, &contents)? {
+        Some(upgraded) => upgraded,
+        None => return Ok(contents),
+    };
+    let backup = backup(config_path, contents.as_bytes())?;
+    fs::write(config_path, &migrated)?;
+    info!(
+        "Migrated configuration from version {version} to {CONFIG_VERSION} (backup at `{}`)",
+        backup.to_string_lossy()
+    );
+    Ok(migrated)
+}
+
+/// Versioning was introduced without changing any settings
+fn v0_to_v1(_document: &mut DocumentMut) {}
+
+/// Replaces the single `author_name` (and the author settings of profiles)
+/// with a list of author records
+fn v1_to_v2(document: &mut DocumentMut) {
+    let global = take_formatted(document.as_table_mut(), "author_name");
+    // The former default is a placeholder: dropping it lets vitex detect the user's identity
+    let global_name = global
+        .as_ref()
+        .and_then(|(_, item)| item.as_str())
+        .filter(|name| *name != V1_DEFAULT_AUTHOR)
+        .map(str::to_string);
+    if let Some(name) = &global_name {
+        insert_formatted(
+            document.as_table_mut(),
+            "authors",
+            Array::from_iter([author_record(name, None, None)]),
+            global.as_ref(),
+        );
+    }
+    let Some(profiles) = document
+        .get_mut("profiles")
+        .and_then(Item::as_table_like_mut)
+    else {
+        return;
+    };
+    for (_, profile) in profiles.iter_mut() {
+        let Some(profile) = profile.as_table_like_mut() else {
+            continue;
+        };
+        let removed = take_formatted(profile, "author_name");
+        let mut take = |key: &str| {
+            profile
+                .remove(key)
+                .and_then(|item| item.as_str().map(str::to_string))
+        };
+        let (email, affiliation) = (take("email"), take("affiliation"));
+        let name = removed
+            .as_ref()
+            .and_then(|(_, item)| item.as_str().map(str::to_string));
+        if name.is_none() && email.is_none() && affiliation.is_none() {
+            continue;
+        }
+        // Profiles which only set an email or affiliation kept the global name
+        let Some(name) = name.or_else(|| global_name.clone()) else {
+            continue;
+        };
+        insert_formatted(
+            profile,
+            "authors",
+            Array::from_iter([author_record(&name, email, affiliation)]),
+            removed.as_ref(),
+        );
+    }
+}
+
+/// Removes a setting and returns the formatting of its key (e.g. the comments above it)
+/// together with its value
+fn take_formatted(table: &mut dyn TableLike, key: &str) -> Option<(Decor, Item)> {
+    let decor = table.key(key)?.leaf_decor().clone();
+    Some((decor, table.remove(key)?))
+}
+
+/// Inserts a setting which replaces a removed one, keeping the comments around it
+fn insert_formatted(
+    table: &mut dyn TableLike,
+    key: &str,
+    new_value: impl Into<Value>,
+    removed: Option<&(Decor, Item)>,
+) {
+    let mut new_value = new_value.into();
+    if let Some(old_value) = removed.and_then(|(_, item)| item.as_value()) {
+        *new_value.decor_mut() = old_value.decor().clone();
+    }
+    table.insert(key, Item::Value(new_value));
+    if let (Some((decor, _)), Some(mut new_key)) = (removed, table.key_mut(key)) {
+        *new_key.leaf_decor_mut() = decor.clone();
+    }
+}
+
+/// Builds an inline `{ name = ..., email = ... }` table
+fn author_record(name: &str, email: Option<String>, affiliation: Option<String>) -> InlineTable {
+    let mut record = InlineTable::new();
+    record.insert("name", name.into());
+    for (key, field) in [("email", email), ("affiliation", affiliation)] {
+        if let Some(field) = field {
+            record.insert(key, field.into());
+        }
+    }
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgraded(contents: &str) -> Option<(u32, String)> {
+        upgrade(Path::new("config.toml"), contents).expect("Configuration should be upgraded")
+    }
+
+    #[test]
+    fn converts_author_settings() {
+        let contents = r#"# The author
+author_name = "Jane Doe" # me
+
+[profiles.work]
+email = "jane@example.org"
+
+[profiles.other]
+author_name = "Other"
+affiliation = "Uni"
+
+[build]
+engine = "latexmk"
+"#;
+        let expected = r#"version = 2
+# The author
+authors = [{ name = "Jane Doe" }] # me
+
+[profiles.work]
+authors = [{ name = "Jane Doe", email = "jane@example.org" }]
+
+[profiles.other]
+authors = [{ name = "Other", affiliation = "Uni" }]
+
+[build]
+engine = "latexmk"
+"#;
+        assert_eq!(upgraded(contents), Some((0, expected.to_string())));
+    }
+
+    #[test]
+    fn drops_the_placeholder_author() {
+        assert_eq!(
+            upgraded("version = 1\nauthor_name = \"John Doe\"\n"),
+            Some((1, "version = 2\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn keeps_current_and_newer_versions() {
+        assert_eq!(upgraded("version = 2\nauthor_name = \"Jane\"\n"), None);
+        assert_eq!(upgraded("version = 3\n"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_versions() {
+        for contents in ["version = -1\n", "version = \"2\"\n"] {
+            assert!(
+                upgrade(Path::new("config.toml"), contents).is_err(),
+                "{contents} was accepted"
+            );
+        }
+    }
+}
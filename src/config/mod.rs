@@ -1,15 +1,60 @@
+mod author;
+mod edit;
 mod errors;
+mod identity;
+mod locate;
+mod migrate;
+mod policy;
+mod profile;
 mod read;
+mod schema;
 mod validate;
+mod write;
 
-pub use read::{file_path, read_config};
+pub use author::{read_authors, Author};
+pub use edit::{set_value, ConfigDocument};
+pub use errors::{Error, Result};
+pub use identity::detect_author;
+pub use migrate::{migrate, upgrade, CONFIG_VERSION};
+pub use policy::RepositoryPolicy;
+pub use profile::{Profile, ProjectDefaults};
+pub use read::{check_config, file_path, read_config};
+pub use validate::{validate_id, validate_path_prefix, ValidateError};
+pub use write::write_config;
 
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+/// Missing settings fall back to their default values
+/// so that settings added in newer versions never break existing files
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
 pub struct Config {
-    pub author_name: String,
+    /// The version of this file's format (managed by vitex)
+    pub version: u32,
+    /// The profile which is applied unless another one is selected (empty for none)
+    pub default_profile: String,
+    /// The default authors of new projects (detected from `$VITEX_AUTHOR`, git or passwd if empty)
+    pub authors: Vec<Author>,
+    /// The templates which projects can be created from (the first one is the default)
     pub templates: Vec<Template>,
+    /// Values for the `VITEX_<KEY>_PLACEHOLDER` placeholders of templates
+    pub variables: BTreeMap<String, String>,
+    /// Named identities which can be selected using `--profile` or `$VITEX_PROFILE`
+    pub profiles: BTreeMap<String, Profile>,
+    /// How `vitex project build` compiles projects
+    pub build: BuildConfig,
+    /// Which auxiliary files `vitex project clean` removes
+    pub clean: CleanConfig,
+    /// Whether new projects are initialized as git repositories
+    pub git: GitConfig,
+    /// How the files of templates are copied into new projects
+    pub copy: CopyConfig,
+    /// Which template repositories may be cloned
+    pub repository_policy: RepositoryPolicy,
 }
 
 impl Default for Config {
@@ -17,7 +62,9 @@ impl Default for Config {
         let repository = env!("CARGO_PKG_REPOSITORY");
 
         Self {
-            author_name: "John Doe".to_string(),
+            version: CONFIG_VERSION,
+            default_profile: String::new(),
+            authors: vec![],
             templates: vec![
                 Template {
                     id: "normal".to_string(),
@@ -25,6 +72,7 @@ impl Default for Config {
                         TemplateGitConfig {
                             repository: repository.to_string(),
                             path_prefix: "templates/normal".to_string(),
+                            rev: None,
                         }
                     },
                 },
@@ -34,22 +82,113 @@ impl Default for Config {
                         TemplateGitConfig {
                             repository: repository.to_string(),
                             path_prefix: "templates/blank".to_string(),
+                            rev: None,
                         }
                     },
                 },
             ],
+            variables: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            build: BuildConfig::default(),
+            clean: CleanConfig::default(),
+            git: GitConfig::default(),
+            copy: CopyConfig::default(),
+            repository_policy: RepositoryPolicy::default(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct Template {
+    /// The unique ID which is used to select the template
     pub id: String,
+    #[serde(default)]
     pub git: TemplateGitConfig,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
 pub struct TemplateGitConfig {
+    /// The URL or path of the repository (empty for templates in the custom templates directory)
     pub repository: String,
+    /// The template's directory inside the repository
     pub path_prefix: String,
+    /// A branch, tag or commit which is checked out instead of the default branch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct BuildConfig {
+    /// Either a preset (`latexmk`, `pdflatex`, `lualatex`, `xelatex`, `tectonic`)
+    /// or a custom command which may use the `{main}` and `{outdir}` placeholders
+    pub engine: String,
+    /// The directory (relative to the project) which holds the build output
+    pub output_dir: String,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self {
+            engine: "latexmk".to_string(),
+            output_dir: "build".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct CleanConfig {
+    /// Additional extensions of auxiliary files which `project clean` removes
+    /// (extensions of source files like `tex` are rejected)
+    pub extensions: Vec<String>,
+}
+
+/// The extensions of source files which `project clean` never removes
+const SOURCE_EXTENSIONS: [&str; 13] = [
+    "tex", "ltx", "sty", "cls", "bib", "bst", "dtx", "ins", "cfg", "def", "bbx", "cbx", "lbx",
+];
+
+impl CleanConfig {
+    /// Whether an extension must not be cleaned as it is empty, is not a plain extension
+    /// or belongs to source files
+    pub fn is_protected(extension: &str) -> bool {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        extension.is_empty()
+            || extension.contains(['/', '\\'])
+            || SOURCE_EXTENSIONS.contains(&extension.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct GitConfig {
+    /// Whether new projects are initialized as git repositories by default
+    pub init: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct CopyConfig {
+    /// How symlinks which point outside of the template are handled
+    pub external_symlinks: SymlinkPolicy,
+    /// Whether files which the template marks as immutable assets are hardlinked instead of copied
+    pub hardlink_assets: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Abort copying the template
+    #[default]
+    Refuse,
+    /// Copy the symlink's target instead
+    Dereference,
 }
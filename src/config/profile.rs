@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{detect_author, errors::Error, Author, Config, Result};
+
+/// A named identity (e.g. `student` or `tutor`) whose settings override the global ones
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(default)]
+#[schemars(deny_unknown_fields)]
+pub struct Profile {
+    /// The template which is used unless another one is specified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// The authors of new projects (overrides `authors`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<Author>>,
+    /// Values which override the global variables of the same name
+    pub variables: BTreeMap<String, String>,
+}
+
+/// The defaults of new projects after applying the selected profile
+pub struct ProjectDefaults {
+    /// The name of the applied profile (if any)
+    pub profile: Option<String>,
+    pub authors: Vec<Author>,
+    pub template: Option<String>,
+    /// Values for the `VITEX_<KEY>_PLACEHOLDER` placeholders of templates
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Applies the given profile (or the default profile if none is given) to the global settings.
+    /// The authors are taken from the first source which specifies any: the given `authors`
+    /// (e.g. from the command line), the profile, the global settings and finally the detected
+    /// identity (`$VITEX_AUTHOR`, git or passwd)
+    pub fn project_defaults(
+        &self,
+        profile: Option<&str>,
+        authors: Vec<Author>,
+    ) -> Result<ProjectDefaults> {
+        let name = profile.or(match self.default_profile.is_empty() {
+            true => None,
+            false => Some(self.default_profile.as_str()),
+        });
+        let mut defaults = ProjectDefaults {
+            profile: name.map(str::to_string),
+            authors: self.authors.clone(),
+            template: None,
+            variables: self.variables.clone(),
+        };
+        if let Some(name) = name {
+            self.apply_profile(name, &mut defaults)?;
+        }
+        if !authors.is_empty() {
+            defaults.authors = authors;
+        } else if defaults.authors.is_empty() {
+            defaults.authors = detect_author().into_iter().collect();
+        }
+        Ok(defaults)
+    }
+
+    fn apply_profile(&self, name: &str, defaults: &mut ProjectDefaults) -> Result<()> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| Error::UnknownProfile(name.to_string()))?;
+        if let Some(authors) = &profile.authors {
+            defaults.authors = authors.clone();
+        }
+        defaults.variables.extend(profile.variables.clone());
+        defaults.template = profile.template.clone();
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
-use super::{errors::Result, Config};
+use log::warn;
+use toml_edit::ImDocument;
+
+use super::{
+    detect_author, errors::Result, locate, migrate, upgrade, write_config, Config, CONFIG_VERSION,
+};
 use std::{
-    env,
-    fs::{self, File},
-    io::Write,
-    path::Path,
+    env, fs,
+    path::{Path, PathBuf},
 };
 
 pub fn file_path() -> Option<String> {
@@ -21,20 +24,61 @@ pub fn file_path() -> Option<String> {
 
 pub fn read_config(config_path: &Path, custom_templates_path: &Path) -> Result<Config> {
     match config_path.exists() {
-        true => Ok(toml::from_str::<Config>(&fs::read_to_string(config_path)?)?
-            .validate(custom_templates_path)?),
+        true => {
+            let contents = migrate(config_path, fs::read_to_string(config_path)?)?;
+            Config::parse(&contents, config_path, custom_templates_path)
+        }
         false => {
-            fs::create_dir_all(
-                config_path
-                    .parent()
-                    .expect("Config file path is expected to have a parent"),
-            )?;
-            let mut file = File::create(config_path)?;
-            file.write_all(
-                &toml::to_vec(&Config::default())
-                    .expect("The config struct must always be encodable"),
-            )?;
-            Ok(Config::default())
+            // Seed the authors with the user's identity instead of a placeholder
+            let config = Config {
+                authors: detect_author().into_iter().collect(),
+                ..Config::default()
+            };
+            write_config(config_path, custom_templates_path, &config)?;
+            Ok(config)
+        }
+    }
+}
+
+/// Validates the configuration file without writing to it
+pub fn check_config(config_path: &Path, custom_templates_path: &Path) -> Result<Config> {
+    let contents = fs::read_to_string(config_path)?;
+    match upgrade(config_path, &contents)? {
+        // Positions refer to the upgraded document which differs from the file on disk
+        Some((version, upgraded)) => Config::parse(
+            &upgraded,
+            &PathBuf::from(format!(
+                "{} (migrated from version {version} to {CONFIG_VERSION})",
+                config_path.to_string_lossy()
+            )),
+            custom_templates_path,
+        ),
+        None => Config::parse(&contents, config_path, custom_templates_path),
+    }
+}
+
+impl Config {
+    /// Decodes and validates a configuration without touching the config file.
+    /// Errors point at their position in the file at `origin`.
+    /// Unknown settings are rejected unless the file was written by a newer version of vitex
+    pub fn parse(contents: &str, origin: &Path, custom_templates_path: &Path) -> Result<Self> {
+        let document = ImDocument::parse(contents)
+            .map_err(|err| locate::syntax_error(origin, contents, err))?;
+        let mut ignored = vec![];
+        let mut deserializer = toml::Deserializer::new(contents);
+        let config: Self = serde_ignored::deserialize(&mut deserializer, |path| {
+            ignored.push(locate::segments(&path))
+        })
+        .map_err(|err| locate::decode_error(origin, contents, err))?;
+        for path in ignored {
+            if config.version > CONFIG_VERSION {
+                warn!("Ignoring unknown setting `{}`", path.join("."));
+                continue;
+            }
+            return Err(locate::unknown_field(origin, &document, &config, &path));
         }
+        config
+            .validate(custom_templates_path)
+            .map_err(|err| locate::validation_error(origin, &document, err))
     }
 }
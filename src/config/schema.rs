@@ -0,0 +1,37 @@
+use schemars::{schema::RootSchema, schema_for};
+
+use crate::templates::Manifest;
+
+use super::{Config, CONFIG_VERSION};
+
+/// Returns the URL which identifies a schema of the current configuration version
+fn schema_id(name: &str) -> String {
+    format!(
+        "{}/schemas/{name}-v{CONFIG_VERSION}.json",
+        env!("CARGO_PKG_REPOSITORY")
+    )
+}
+
+impl Config {
+    /// Returns the JSON Schema of the configuration file (e.g. for TOML language servers)
+    pub fn schema() -> RootSchema {
+        let mut schema = schema_for!(Config);
+        let metadata = schema.schema.metadata();
+        metadata.id = Some(schema_id("config"));
+        metadata.title = Some(format!("vitex configuration (version {CONFIG_VERSION})"));
+        schema
+    }
+}
+
+impl Manifest {
+    /// Returns the JSON Schema of template manifests (`vitex.toml`)
+    pub fn schema() -> RootSchema {
+        let mut schema = schema_for!(Manifest);
+        let metadata = schema.schema.metadata();
+        metadata.id = Some(schema_id("manifest"));
+        metadata.title = Some(format!(
+            "vitex template manifest (version {CONFIG_VERSION})"
+        ));
+        schema
+    }
+}
@@ -1,10 +1,48 @@
-use std::{fmt::Display, path::Path};
+use std::{
+    error,
+    fmt::Display,
+    path::{Component, Path},
+};
 
-use super::Config;
+use crate::error::{Category, Diagnose};
 
+use super::{CleanConfig, Config};
+
+/// The maximum length of a template ID
+const MAX_ID_LENGTH: usize = 64;
+
+#[derive(Debug)]
 pub enum ValidateError {
     DuplicateID(String),
-    InvalidPath { id: String, path: String },
+    InvalidPath {
+        id: String,
+        path: String,
+    },
+    InvalidID {
+        id: String,
+        reason: String,
+    },
+    UnsafePathPrefix {
+        id: String,
+        path_prefix: String,
+        reason: String,
+    },
+    RepositoryRejected {
+        id: String,
+        repository: String,
+        reason: String,
+    },
+    UnknownDefaultProfile(String),
+    UnknownProfileTemplate {
+        profile: String,
+        template: String,
+    },
+    ProtectedCleanExtension(String),
+    InvalidVariable {
+        /// The profile which defines the variable (if not global)
+        profile: Option<String>,
+        key: String,
+    },
 }
 
 impl Display for ValidateError {
@@ -15,16 +53,95 @@ impl Display for ValidateError {
             match self {
                 Self::DuplicateID(id) => format!("ID `{id}` is duplicated but must be unique"),
                 Self::InvalidPath { id, path } =>
-                    format!("Template `{id}` was not found at local path: (expected: `{path}`):\nHINT: Template is expected to be local due to empty git repository"),
+                    format!("Template `{id}` was not found at local path (expected: `{path}`)"),
+                Self::InvalidID { id, reason } =>
+                    format!("Template ID `{id}` is invalid: {reason}"),
+                Self::UnsafePathPrefix {
+                    id,
+                    path_prefix,
+                    reason,
+                } => format!("Path prefix `{path_prefix}` of template `{id}` is unsafe: {reason}"),
+                Self::RepositoryRejected {
+                    id,
+                    repository,
+                    reason,
+                } =>
+                    format!("Repository `{repository}` of template `{id}` is not allowed: {reason}"),
+                Self::UnknownDefaultProfile(name) =>
+                    format!("Default profile `{name}` is not configured"),
+                Self::UnknownProfileTemplate { profile, template } =>
+                    format!("Template `{template}` of profile `{profile}` is not configured"),
+                Self::ProtectedCleanExtension(extension) =>
+                    format!("Extension `{extension}` of `clean.extensions` cannot be cleaned"),
+                Self::InvalidVariable { profile: None, key } =>
+                    format!("Variable name `{key}` is invalid"),
+                Self::InvalidVariable {
+                    profile: Some(profile),
+                    key,
+                } => format!("Variable name `{key}` of profile `{profile}` is invalid"),
+            }
+        )
+    }
+}
+
+impl error::Error for ValidateError {}
+
+impl Diagnose for ValidateError {
+    fn category(&self) -> Category {
+        Category::Config
+    }
+
+    fn hint(&self) -> Option<String> {
+        Some(
+            match self {
+                Self::DuplicateID(_) => "rename or remove one of the templates",
+                Self::InvalidPath { .. } => "Template is expected to be local due to empty git repository",
+                Self::InvalidID { .. } => "IDs may only contain letters, digits, `-`, `_` and `.` and must start with a letter or digit",
+                Self::UnsafePathPrefix { .. } => "the path prefix must be a relative path inside the template's repository",
+                Self::RepositoryRejected { .. } => "check the `repository_policy` section of the configuration",
+                Self::UnknownDefaultProfile(_) => "add the profile to `profiles` or clear `default_profile`",
+                Self::UnknownProfileTemplate { .. } => "use the ID of a configured template",
+                Self::ProtectedCleanExtension(_) => "only list extensions of auxiliary files: source files (e.g. `.tex`, `.sty` or `.bib`) are never removed",
+                Self::InvalidVariable { .. } => "variable names may only contain lowercase letters, digits and `_` as they are used in `VITEX_<KEY>_PLACEHOLDER`",
             }
+            .to_string(),
         )
     }
 }
 
+impl ValidateError {
+    /// The ID of the template which caused the error (if any)
+    pub fn template_id(&self) -> Option<&str> {
+        match self {
+            Self::DuplicateID(id)
+            | Self::InvalidPath { id, .. }
+            | Self::InvalidID { id, .. }
+            | Self::UnsafePathPrefix { id, .. }
+            | Self::RepositoryRejected { id, .. } => Some(id),
+            Self::UnknownDefaultProfile(_)
+            | Self::UnknownProfileTemplate { .. }
+            | Self::ProtectedCleanExtension(_)
+            | Self::InvalidVariable { .. } => None,
+        }
+    }
+}
+
 impl Config {
     pub fn validate(self, custom_base_path: &Path) -> Result<Self, ValidateError> {
         let mut ids: Vec<&str> = Vec::with_capacity(self.templates.len());
         for template in &self.templates {
+            // IDs and path prefixes are joined onto vitex's directories
+            validate_id(&template.id)?;
+            validate_path_prefix(&template.id, &template.git.path_prefix)?;
+            if !template.git.repository.is_empty() {
+                if let Err(reason) = self.repository_policy.check(&template.git.repository) {
+                    return Err(ValidateError::RepositoryRejected {
+                        id: template.id.clone(),
+                        repository: template.git.repository.clone(),
+                        reason,
+                    });
+                }
+            }
             if ids.contains(&template.id.as_str()) {
                 return Err(ValidateError::DuplicateID(template.id.clone()));
             }
@@ -40,6 +157,97 @@ impl Config {
                 });
             }
         }
+        if !self.default_profile.is_empty() && !self.profiles.contains_key(&self.default_profile) {
+            return Err(ValidateError::UnknownDefaultProfile(
+                self.default_profile.clone(),
+            ));
+        }
+        if let Some(extension) = self
+            .clean
+            .extensions
+            .iter()
+            .find(|extension| CleanConfig::is_protected(extension))
+        {
+            return Err(ValidateError::ProtectedCleanExtension(extension.clone()));
+        }
+        validate_variables(None, self.variables.keys())?;
+        for (name, profile) in &self.profiles {
+            validate_variables(Some(name), profile.variables.keys())?;
+            if let Some(template) = &profile.template {
+                if !ids.contains(&template.as_str()) {
+                    return Err(ValidateError::UnknownProfileTemplate {
+                        profile: name.clone(),
+                        template: template.clone(),
+                    });
+                }
+            }
+        }
         Ok(self)
     }
 }
+
+/// Ensures that an ID can safely be used as a directory name
+pub fn validate_id(id: &str) -> Result<(), ValidateError> {
+    let reason = if id.is_empty() {
+        Some("it is empty".to_string())
+    } else if id.len() > MAX_ID_LENGTH {
+        Some(format!("it is longer than {MAX_ID_LENGTH} characters"))
+    } else if !id.starts_with(|char: char| char.is_ascii_alphanumeric()) {
+        Some("it does not start with a letter or digit".to_string())
+    } else {
+        id.chars()
+            .find(|char| !char.is_ascii_alphanumeric() && !matches!(char, '-' | '_' | '.'))
+            .map(|char| format!("it contains the character `{char}`"))
+    };
+    match reason {
+        Some(reason) => Err(ValidateError::InvalidID {
+            id: id.to_string(),
+            reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Ensures that variable names map to placeholders which can be written in templates
+fn validate_variables<'k>(
+    profile: Option<&String>,
+    mut keys: impl Iterator<Item = &'k String>,
+) -> Result<(), ValidateError> {
+    let invalid = keys.find(|key| {
+        key.is_empty()
+            || !key
+                .chars()
+                .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '_')
+    });
+    match invalid {
+        Some(key) => Err(ValidateError::InvalidVariable {
+            profile: profile.cloned(),
+            key: key.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Ensures that a path prefix stays inside the template's repository after normalization
+pub fn validate_path_prefix(id: &str, path_prefix: &str) -> Result<(), ValidateError> {
+    let error = |reason: &str| {
+        Err(ValidateError::UnsafePathPrefix {
+            id: id.to_string(),
+            path_prefix: path_prefix.to_string(),
+            reason: reason.to_string(),
+        })
+    };
+    let mut depth: usize = 0;
+    for component in Path::new(path_prefix).components() {
+        match component {
+            Component::RootDir | Component::Prefix(_) => return error("it is an absolute path"),
+            Component::ParentDir if depth == 0 => {
+                return error("it leads outside of the repository")
+            }
+            Component::ParentDir => depth -= 1,
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+        }
+    }
+    Ok(())
+}
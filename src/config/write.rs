@@ -0,0 +1,119 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use log::info;
+use schemars::schema::Schema;
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::{errors::Result, Config};
+
+/// The comment at the top of written configuration files
+const HEADER: &str = "# vitex configuration
+# Run `vitex config schema` for a description of all settings
+";
+
+/// Validates the configuration and writes it (including explanatory comments) to the config file.
+/// An existing file is backed up next to it (without replacing earlier backups) before it is overwritten
+pub fn write_config(
+    config_path: &Path,
+    custom_templates_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let contents = commented(config);
+    Config::parse(&contents, config_path, custom_templates_path)?;
+    if config_path.exists() {
+        let backup = backup(config_path, &fs::read(config_path)?)?;
+        info!(
+            "Backed up the previous configuration to `{}`",
+            backup.to_string_lossy()
+        );
+    } else if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(config_path, contents)?;
+    Ok(())
+}
+
+/// Writes the contents to the first free `<config>.<n>.bak` and returns its path
+pub(super) fn backup(config_path: &Path, contents: &[u8]) -> io::Result<PathBuf> {
+    for number in 1.. {
+        let mut backup = config_path.as_os_str().to_owned();
+        backup.push(format!(".{number}.bak"));
+        // Creating the file exclusively never replaces an existing backup
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&backup)
+        {
+            Ok(mut file) => {
+                file.write_all(contents)?;
+                return Ok(backup.into());
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!("There is always a free backup number")
+}
+
+/// Encodes the configuration with its description from the schema above each top-level setting
+fn commented(config: &Config) -> String {
+    let mut document: DocumentMut = toml::to_string(config)
+        .expect("The config struct must always be encodable")
+        .parse()
+        .expect("The encoded config must always be valid TOML");
+    let schema = Config::schema();
+    let properties = &schema
+        .schema
+        .object
+        .as_ref()
+        .expect("The config schema must describe an object")
+        .properties;
+    for (key, property) in properties {
+        let Some(description) = description(property) else {
+            continue;
+        };
+        let comment: Vec<String> = description
+            .lines()
+            .map(|line| format!("# {line}"))
+            .collect();
+        let prefix = format!("\n{}\n", comment.join("\n"));
+        match document.get_mut(key) {
+            Some(Item::Table(table)) => comment_table(table, prefix),
+            Some(Item::ArrayOfTables(array)) => {
+                if let Some(table) = array.get_mut(0) {
+                    table.decor_mut().set_prefix(prefix);
+                }
+            }
+            Some(Item::Value(_)) => {
+                if let Some(mut key) = document.key_mut(key) {
+                    key.leaf_decor_mut().set_prefix(prefix);
+                }
+            }
+            _ => {}
+        }
+    }
+    format!("{HEADER}\n{}", document.to_string().trim_start())
+}
+
+/// The description of a setting in the schema
+fn description(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Object(object) => object.metadata.as_ref()?.description.as_deref(),
+        Schema::Bool(_) => None,
+    }
+}
+
+/// Comments a table or, if it is implicit (e.g. `profiles`), its first displayed subtable
+fn comment_table(table: &mut Table, prefix: String) {
+    if !table.is_implicit() {
+        table.decor_mut().set_prefix(prefix);
+        return;
+    }
+    if let Some((_, Item::Table(subtable))) = table.iter_mut().next() {
+        comment_table(subtable, prefix);
+    }
+}
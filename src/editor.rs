@@ -0,0 +1,59 @@
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::Path,
+    process::Command,
+};
+
+use log::{error, info};
+use vitex::{
+    config::{self, Config},
+    error::Report,
+};
+
+use crate::prompt;
+
+/// Opens the configuration file in the user's editor until it contains a valid configuration.
+/// A missing file is first created like on the first run.
+/// If the user gives up, the previous contents of the file are restored
+pub fn edit_config(config_path: &Path, custom_templates_path: &Path) -> config::Result<Config> {
+    if !config_path.exists() {
+        config::read_config(config_path, custom_templates_path)?;
+        info!(
+            "Created the configuration file `{}`",
+            config_path.to_string_lossy()
+        );
+    }
+    let previous = fs::read_to_string(config_path)?;
+    loop {
+        open_editor(config_path)?;
+        let err = match config::read_config(config_path, custom_templates_path) {
+            Ok(config) => return Ok(config),
+            Err(err) => err,
+        };
+        error!("{}", Report::new("Configuration is invalid", &err));
+        if !prompt::confirm("Reopen the editor?", true)? {
+            fs::write(config_path, previous)?;
+            info!("Restored the previous configuration");
+            return Err(err);
+        }
+    }
+}
+
+/// Runs `$VISUAL` or `$EDITOR` (falling back to `vi`) and waits for it to exit
+fn open_editor(path: &Path) -> io::Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "editor command is empty"))?;
+    let status = Command::new(program).args(words).arg(path).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "editor `{editor}` exited with {status}"
+        )));
+    }
+    Ok(())
+}
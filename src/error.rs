@@ -0,0 +1,207 @@
+use std::{error::Error, fmt::Display, io, ops::Range, path::Path};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The categories of failures.
+/// Each category has its own exit code so that scripts can tell failures apart:
+///
+/// | Code | Category              | Examples                                       |
+/// |------|-----------------------|------------------------------------------------|
+/// | 1    | other                 | unexpected failures                            |
+/// | 2    | user input            | unknown template, existing project directory   |
+/// | 3    | config                | malformed `config.toml`, rejected repository   |
+/// | 4    | template validation   | template not synced, missing placeholders      |
+/// | 5    | git                   | failed clone or pull                           |
+/// | 6    | io                    | permission denied, disk full                   |
+/// | 7    | build                 | LaTeX errors, engine not installed             |
+/// | 8    | conflict              | merge conflicts after `project update`         |
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Other,
+    UserInput,
+    Config,
+    TemplateValidation,
+    Git,
+    IO,
+    Build,
+    Conflict,
+}
+
+impl Category {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Other => 1,
+            Self::UserInput => 2,
+            Self::Config => 3,
+            Self::TemplateValidation => 4,
+            Self::Git => 5,
+            Self::IO => 6,
+            Self::Build => 7,
+            Self::Conflict => 8,
+        }
+    }
+}
+
+/// Common behaviour of all errors which are reported to the user
+pub trait Diagnose: Error {
+    /// The category which determines the exit code
+    fn category(&self) -> Category;
+
+    /// An actionable suggestion on how to resolve the error
+    fn hint(&self) -> Option<String> {
+        None
+    }
+
+    /// The positions in files which caused the error
+    fn locations(&self) -> Vec<Location> {
+        vec![]
+    }
+}
+
+/// A position in a file which is shown below an error
+#[derive(Clone, Debug, Serialize)]
+pub struct Location {
+    pub file: String,
+    /// The line number (starting at 1)
+    pub line: usize,
+    /// The column in characters (starting at 1)
+    pub column: usize,
+    /// The contents of the line
+    pub text: String,
+    /// The number of highlighted characters
+    pub length: usize,
+    /// A note next to the highlighted characters
+    pub label: Option<String>,
+}
+
+impl Location {
+    /// Locates a byte range of a file's contents
+    pub fn from_span(file: &Path, contents: &str, span: Range<usize>, label: Option<&str>) -> Self {
+        let start = span.start.min(contents.len());
+        let line_start = contents[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = contents[start..]
+            .find('\n')
+            .map_or(contents.len(), |index| start + index);
+        let end = span.end.clamp(start, line_end);
+        Self {
+            file: file.to_string_lossy().to_string(),
+            line: contents[..line_start].matches('\n').count() + 1,
+            column: contents[line_start..start].chars().count() + 1,
+            text: contents[line_start..line_end].trim_end().to_string(),
+            length: contents[start..end].trim_end().chars().count().max(1),
+            label: label.map(str::to_string),
+        }
+    }
+
+    /// Locates a (zero-based) line and byte column of a file's contents
+    pub fn from_line_col(
+        file: &Path,
+        contents: &str,
+        line: usize,
+        column: usize,
+        label: Option<&str>,
+    ) -> Self {
+        let line_start: usize = contents
+            .split_inclusive('\n')
+            .take(line)
+            .map(str::len)
+            .sum();
+        let start = line_start + column;
+        Self::from_span(file, contents, start..start + 1, label)
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        write!(
+            f,
+            "{gutter}--> {}:{}:{}\n{gutter} |\n{} | {}\n{gutter} | {}{}",
+            self.file,
+            self.line,
+            self.column,
+            self.line,
+            self.text,
+            " ".repeat(self.column - 1),
+            "^".repeat(self.length),
+        )?;
+        if let Some(label) = &self.label {
+            write!(f, " {label}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Diagnose for io::Error {
+    fn category(&self) -> Category {
+        Category::IO
+    }
+}
+
+/// A failure including its cause chain, ready to be presented to the user
+pub struct Report {
+    pub message: String,
+    pub causes: Vec<String>,
+    pub hint: Option<String>,
+    pub locations: Vec<Location>,
+    pub category: Category,
+}
+
+impl Report {
+    /// Creates a report of an error, prefixed by what was attempted
+    pub fn new(context: &str, err: &dyn Diagnose) -> Self {
+        let mut causes = vec![];
+        let mut source = err.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        Self {
+            message: format!("{context}: {err}"),
+            causes,
+            hint: err.hint(),
+            locations: err.locations(),
+            category: err.category(),
+        }
+    }
+
+    /// Creates a report of a failure which has no underlying error
+    pub fn message(category: Category, message: impl Display) -> Self {
+        Self {
+            message: message.to_string(),
+            causes: vec![],
+            hint: None,
+            locations: vec![],
+            category,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "message": self.message,
+            "causes": self.causes,
+            "hint": self.hint,
+            "locations": self.locations,
+            "category": self.category,
+            "exit_code": self.category.exit_code(),
+        })
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        for cause in &self.causes {
+            write!(f, "\n  caused by: {cause}")?;
+        }
+        for location in &self.locations {
+            write!(f, "\n{location}")?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\nHINT: {hint}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+//! Vitex creates, updates and builds LaTeX projects from templates.
+//!
+//! The `vitex` binary is a thin command line wrapper around this library.
+//! Failures are returned as errors implementing [`error::Diagnose`]
+//! and progress is reported through the [`log`] facade.
+
+pub mod build;
+pub mod config;
+pub mod error;
+pub mod project;
+pub mod templates;
+
+pub use config::Config;
+pub use project::ProjectBuilder;
+pub use templates::TemplateRegistry;
@@ -1,21 +1,36 @@
-use std::{path::Path, process};
+use std::{path::Path, process, slice, time::Duration};
 
 use clap::Parser;
 use cli::{Args, Command, ProjectCommand};
-use log::{error, info, Level};
-use loggerv::Logger;
+use log::{error, info, warn, Level};
+use loggerv::{Logger, Output};
+use serde_json::{json, Value};
 
-use crate::cli::TemplateCommand;
+use vitex::{
+    build,
+    config::{self, Author, BuildConfig, ProjectDefaults, Template, TemplateGitConfig},
+    error::{Category, Diagnose, Report},
+    project,
+    templates::{self, TemplatePaths},
+    Config, ProjectBuilder, TemplateRegistry,
+};
+
+use crate::{
+    cli::{ConfigCommand, InitArgs, TemplateCommand},
+    output::OutputFormat,
+};
 use log::debug;
 
 mod cli;
-mod config;
-mod project;
-mod templates;
+mod editor;
+mod output;
+mod prompt;
+mod wizard;
 
 fn main() {
     let args = Args::parse();
-    Logger::new()
+    let output = args.output;
+    let mut logger = Logger::new()
         .max_level(if args.verbose {
             Level::Trace
         } else {
@@ -24,21 +39,80 @@ fn main() {
         .colors(true)
         .level(true)
         .module_path_filters(vec![env!("CARGO_PKG_NAME").replace('-', "_")])
-        .module_path(false)
-        .init()
-        .unwrap();
+        .module_path(false);
+    // Keep stdout free for the structured results
+    if output == OutputFormat::Json {
+        for level in [Level::Info, Level::Debug, Level::Trace] {
+            logger = logger.output(&level, Output::Stderr);
+        }
+    }
+    logger.init().unwrap_or_else(|err| {
+        exit(
+            output,
+            Report::message(
+                Category::Other,
+                format!("Could not initialize logger: {err}"),
+            ),
+            Value::Null,
+        )
+    });
 
     let base_path = config::file_path().unwrap_or_else(|| {
-        error!("Could not determine a config base path: do you have a home-directory?");
-        process::exit(1);
+        exit(
+            output,
+            Report::message(
+                Category::Config,
+                "Could not determine a config base path: do you have a home-directory?",
+            ),
+            Value::Null,
+        )
     });
     let base_path = Path::new(&base_path);
 
+    let template_paths = templates::template_paths(base_path)
+        .unwrap_or_else(|err| fail(output, "Could not determine template paths", &err));
+    let config_path = base_path.join("config.toml");
+    // Validating and editing must not depend on a valid configuration
+    match args.command {
+        Command::Config {
+            command: Some(ConfigCommand::Validate),
+        } => {
+            config::check_config(&config_path, &template_paths.custom)
+                .unwrap_or_else(|err| fail(output, "Configuration is invalid", &err));
+            info!("Configuration is valid.");
+            output.success(json!({ "path": config_path }));
+            return;
+        }
+        Command::Config {
+            command: Some(ConfigCommand::Edit),
+        } => {
+            editor::edit_config(&config_path, &template_paths.custom)
+                .unwrap_or_else(|err| fail(output, "Could not edit configuration", &err));
+            info!("Configuration is valid.");
+            output.success(json!({ "path": config_path }));
+            return;
+        }
+        Command::Config {
+            command: Some(ConfigCommand::Schema { manifest }),
+        } => {
+            let schema = match manifest {
+                true => templates::Manifest::schema(),
+                false => Config::schema(),
+            };
+            if output == OutputFormat::Text {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&schema)
+                        .expect("The schema must always be encodable")
+                );
+            }
+            output.success(json!({ "schema": schema }));
+            return;
+        }
+        _ => {}
+    }
+
     // Create the template directories
-    let template_paths = templates::template_paths(base_path).unwrap_or_else(|err| {
-        error!("Could not determine template paths: {err}");
-        process::exit(1);
-    });
     templates::create_templates_directory(&template_paths.custom, &template_paths.cloned)
         .unwrap_or_else(|err| {
             error!(
@@ -53,40 +127,97 @@ fn main() {
                     .expect("Path should be a String")
             )
         });
+    // Set up the configuration on request or when vitex is run interactively for the first time
+    let init = match &args.command {
+        Command::Init(init) => Some(init.clone()),
+        _ if !config_path.exists() && output == OutputFormat::Text && wizard::is_interactive() => {
+            info!("No configuration found: starting the setup...");
+            Some(InitArgs::default())
+        }
+        _ => None,
+    };
+    if let Some(init) = init {
+        let result = init_config(output, &config_path, &template_paths, init);
+        if let Command::Init(_) = args.command {
+            output.success(result);
+            return;
+        }
+    }
     // Read or create the config file
-    let conf = config::read_config(&base_path.join("config.toml"), &template_paths.custom)
-        .unwrap_or_else(|err| {
-            error!("Could not read or create config file: {err}");
-            process::exit(1);
-        });
+    let conf = config::read_config(&config_path, &template_paths.custom)
+        .unwrap_or_else(|err| fail(output, "Could not read or create config file", &err));
+    let registry = TemplateRegistry::from_config(&conf, template_paths);
 
-    match args.command {
+    let result: Value = match args.command {
         Command::Templates(command) => match command {
             TemplateCommand::Sync => {
-                debug!("Syncing {} templates...", conf.templates.len());
-                templates::sync_git(&conf.templates, &template_paths.cloned).unwrap_or_else(|err| {
-                    error!("Could not sync templates: {err}");
-                    process::exit(1);
-                })
+                debug!("Syncing {} templates...", registry.templates().len());
+                let synced = registry
+                    .sync()
+                    .unwrap_or_else(|err| fail(output, "Could not sync templates", &err));
+                json!({ "templates": synced })
             }
             TemplateCommand::Validate => {
-                for path in [template_paths.cloned, template_paths.custom] {
-                    templates::validate_templates(&conf.templates, &path).unwrap_or_else(|err| {
-                        error!("Validation detected an issue:\n{err}");
-                        process::exit(1);
-                    });
-                }
+                registry
+                    .validate()
+                    .unwrap_or_else(|err| fail(output, "Validation detected an issue", &err));
                 info!(
                     "Scanned {} template(s). No issues detected.",
-                    conf.templates.len()
+                    registry.templates().len()
                 );
+                json!({ "scanned": registry.templates().len() })
+            }
+            TemplateCommand::List => {
+                if output == OutputFormat::Text {
+                    list_templates(registry.templates());
+                }
+                json!({ "templates": registry.templates() })
+            }
+            TemplateCommand::Purge => {
+                registry
+                    .purge()
+                    .unwrap_or_else(|err| fail(output, "Could not purge cloned templates", &err));
+                json!({})
+            }
+            TemplateCommand::Add {
+                id,
+                git,
+                path_prefix,
+                rev,
+                local,
+                sync,
+            } => {
+                let template = Template {
+                    id,
+                    git: TemplateGitConfig {
+                        repository: git.unwrap_or_default(),
+                        path_prefix,
+                        rev,
+                    },
+                };
+                templates::add_template(
+                    &config_path,
+                    registry.paths(),
+                    &template,
+                    local.as_deref().map(Path::new),
+                )
+                .unwrap_or_else(|err| fail(output, "Could not add template", &err));
+                let synced = match sync {
+                    true => templates::sync_git(
+                        slice::from_ref(&template),
+                        &registry.paths().cloned,
+                        registry.policy(),
+                    )
+                    .unwrap_or_else(|err| fail(output, "Could not sync template", &err)),
+                    false => vec![],
+                };
+                json!({ "template": template, "synced": synced })
+            }
+            TemplateCommand::Remove { id } => {
+                let template = templates::remove_template(&config_path, registry.paths(), &id)
+                    .unwrap_or_else(|err| fail(output, "Could not remove template", &err));
+                json!({ "template": template })
             }
-            TemplateCommand::List => templates::list_templates(&conf.templates),
-            TemplateCommand::Purge => templates::purge_cloned(&template_paths.cloned)
-                .unwrap_or_else(|err| {
-                    error!("Could not purge cloned templates: {err}");
-                    process::exit(1);
-                }),
         },
         Command::Project(command) => match command {
             ProjectCommand::New {
@@ -94,26 +225,339 @@ fn main() {
                 subtitle,
                 template,
                 author,
-            } => project::create(
-                &conf.templates,
-                template.as_deref(),
-                &title,
-                &author.unwrap_or(conf.author_name),
-                subtitle.as_deref(),
-                &template_paths,
-                Path::new(""),
-            )
-            .unwrap_or_else(|err| {
-                error!("Could not create new project: {err}");
-                process::exit(1);
-            }),
+                authors_file,
+                git,
+                no_git,
+                hardlink_assets,
+            } => {
+                let defaults = conf
+                    .project_defaults(
+                        args.profile.as_deref(),
+                        cli_authors(output, author, authors_file),
+                    )
+                    .unwrap_or_else(|err| fail(output, "Could not apply profile", &err));
+                let path = project_builder(&registry, defaults, &title, template, subtitle)
+                    .git((conf.git.init || git) && !no_git)
+                    .output_dir(&conf.build.output_dir)
+                    .copy_config(config::CopyConfig {
+                        hardlink_assets: conf.copy.hardlink_assets || hardlink_assets,
+                        ..conf.copy
+                    })
+                    .create(Path::new(""))
+                    .unwrap_or_else(|err| fail(output, "Could not create new project", &err));
+                json!({ "path": path })
+            }
+            ProjectCommand::Init {
+                title,
+                directory,
+                on_conflict,
+                subtitle,
+                template,
+                author,
+                authors_file,
+                git,
+                no_git,
+                hardlink_assets,
+            } => {
+                let defaults = conf
+                    .project_defaults(
+                        args.profile.as_deref(),
+                        cli_authors(output, author, authors_file),
+                    )
+                    .unwrap_or_else(|err| fail(output, "Could not apply profile", &err));
+                project_builder(&registry, defaults, &title, template, subtitle)
+                    .git((conf.git.init || git) && !no_git)
+                    .output_dir(&conf.build.output_dir)
+                    .copy_config(config::CopyConfig {
+                        hardlink_assets: conf.copy.hardlink_assets || hardlink_assets,
+                        ..conf.copy
+                    })
+                    .init(Path::new(&directory), on_conflict.into())
+                    .unwrap_or_else(|err| fail(output, "Could not initialize project", &err));
+                json!({ "path": directory })
+            }
+            ProjectCommand::Update { directory } => {
+                project::update(
+                    registry.templates(),
+                    registry.paths(),
+                    Path::new(&directory),
+                )
+                .unwrap_or_else(|err| fail(output, "Could not update project", &err));
+                json!({ "path": directory })
+            }
+            ProjectCommand::Build {
+                directory,
+                engine,
+                output_dir,
+            } => {
+                let build_config = build_config(conf.build, engine, output_dir);
+                let project_path = Path::new(&directory);
+                match build::build(&build_config, project_path) {
+                    Ok(diagnostics) => {
+                        build::log_diagnostics(&diagnostics);
+                        build::log_success(&build_config, project_path, &diagnostics);
+                        json!({
+                            "output_dir": project_path.join(&build_config.output_dir),
+                            "diagnostics": diagnostics,
+                        })
+                    }
+                    Err(err) => {
+                        let mut details = Value::Null;
+                        if let build::Error::Failed { diagnostics, .. } = &err {
+                            build::log_diagnostics(diagnostics);
+                            details = json!({ "diagnostics": diagnostics });
+                        }
+                        exit(
+                            output,
+                            Report::new("Could not build project", &err),
+                            details,
+                        )
+                    }
+                }
+            }
+            ProjectCommand::Watch {
+                directory,
+                engine,
+                output_dir,
+                debounce,
+            } => {
+                let build_config = build_config(conf.build, engine, output_dir);
+                build::watch(
+                    &build_config,
+                    Path::new(&directory),
+                    Duration::from_millis(debounce),
+                )
+                .unwrap_or_else(|err| fail(output, "Could not watch project", &err));
+                json!({})
+            }
+            ProjectCommand::Clean {
+                directory,
+                dry_run,
+                all,
+            } => {
+                let removed = project::clean(
+                    &conf.clean,
+                    &conf.build.output_dir,
+                    registry.templates(),
+                    registry.paths(),
+                    Path::new(&directory),
+                    all,
+                    dry_run,
+                )
+                .unwrap_or_else(|err| fail(output, "Could not clean project", &err));
+                match dry_run {
+                    true => info!("Would remove {} file(s).", removed.len()),
+                    false => info!("Removed {} file(s).", removed.len()),
+                }
+                json!({ "dry_run": dry_run, "files": removed })
+            }
+            ProjectCommand::Set {
+                key,
+                value,
+                directory,
+                rename,
+            } => {
+                let path = project::set(Path::new(&directory), &key, &value, rename)
+                    .unwrap_or_else(|err| fail(output, &format!("Could not set `{key}`"), &err));
+                json!({ "path": path, "key": key, "value": value })
+            }
+        },
+        Command::Init(_) => unreachable!("The setup is handled before reading the configuration"),
+        Command::Config { command } => match command {
+            None => {
+                info!(
+                    "Configuration file is located at: `{}`",
+                    config_path
+                        .to_str()
+                        .expect("Path is expected to be a valid string")
+                );
+                json!({ "path": config_path })
+            }
+            Some(ConfigCommand::Get { key }) => {
+                let value = conf
+                    .get(&key)
+                    .unwrap_or_else(|err| fail(output, "Could not get setting", &err));
+                if output == OutputFormat::Text {
+                    print_setting(&key, &value);
+                }
+                json!({ "key": key, "value": value })
+            }
+            Some(ConfigCommand::Set { key, value }) => {
+                let conf = config::set_value(&config_path, &registry.paths().custom, &key, &value)
+                    .unwrap_or_else(|err| fail(output, &format!("Could not set `{key}`"), &err));
+                let setting = conf
+                    .get(&key)
+                    .unwrap_or_else(|err| fail(output, "Could not get setting", &err));
+                match setting {
+                    // Arrays of tables would be printed as `[[]]` sections
+                    toml::Value::Array(_) | toml::Value::Table(_) => {
+                        info!("Set `{key}` to {value}")
+                    }
+                    _ => info!("Set `{key}` to {setting}"),
+                }
+                json!({ "key": key, "value": setting })
+            }
+            Some(ConfigCommand::Validate | ConfigCommand::Edit | ConfigCommand::Schema { .. }) => {
+                unreachable!("These commands are handled before reading the configuration")
+            }
+            Some(ConfigCommand::Show) => {
+                if output == OutputFormat::Text {
+                    print!(
+                        "{}",
+                        toml::to_string(&conf).expect("The config struct must always be encodable")
+                    );
+                }
+                json!(conf)
+            }
         },
-        Command::Config => info!(
-            "Configuration file is located at: `{}`",
-            base_path
-                .join("config.toml")
-                .to_str()
-                .expect("Path is expected to be a valid string")
-        ),
+    };
+    output.success(result);
+}
+
+/// Reports the error and exits the program using the exit code of its category
+fn fail(output: OutputFormat, context: &str, err: &dyn Diagnose) -> ! {
+    exit(output, Report::new(context, err), Value::Null)
+}
+
+/// Reports the failure including structured details and exits the program
+fn exit(output: OutputFormat, report: Report, details: Value) -> ! {
+    error!("{report}");
+    output.error(&report, details);
+    process::exit(report.category.exit_code());
+}
+
+/// Runs the setup and writes its configuration (backing up an existing one).
+/// Afterwards, the templates are synced if requested
+fn init_config(
+    output: OutputFormat,
+    config_path: &Path,
+    paths: &TemplatePaths,
+    init: InitArgs,
+) -> Value {
+    let interactive = !init.yes && wizard::is_interactive();
+    wizard::check_builtin(&init.template).unwrap_or_else(|message| {
+        exit(
+            output,
+            Report::message(Category::UserInput, message),
+            Value::Null,
+        )
+    });
+    if config_path.exists() && !init.force {
+        let overwrite = interactive
+            && prompt::confirm(
+                "Overwrite the existing configuration (a backup is kept)?",
+                false,
+            )
+            .unwrap_or_else(|err| fail(output, "Could not read the answer", &err));
+        if !overwrite {
+            exit(
+                output,
+                Report {
+                    hint: Some("pass `--force` to overwrite it".to_string()),
+                    ..Report::message(Category::Conflict, "Configuration file already exists")
+                },
+                json!({ "path": config_path }),
+            );
+        }
+    }
+    let (conf, sync) = wizard::setup(init, interactive)
+        .unwrap_or_else(|err| fail(output, "Could not complete the setup", &err));
+    config::write_config(config_path, &paths.custom, &conf)
+        .unwrap_or_else(|err| fail(output, "Could not write configuration", &err));
+    info!(
+        "Wrote configuration to `{}`",
+        config_path.to_str().expect("Path should be a String")
+    );
+    let synced = match sync {
+        true => templates::sync_git(&conf.templates, &paths.cloned, &conf.repository_policy)
+            .unwrap_or_else(|err| fail(output, "Could not sync templates", &err)),
+        false => vec![],
+    };
+    json!({ "path": config_path, "config": conf, "synced": synced })
+}
+
+/// Prepares the creation of a project using the optional command line arguments,
+/// falling back to the defaults of the selected profile
+fn project_builder<'r>(
+    registry: &'r TemplateRegistry,
+    defaults: ProjectDefaults,
+    title: &str,
+    template: Option<String>,
+    subtitle: Option<String>,
+) -> ProjectBuilder<'r> {
+    if let Some(profile) = &defaults.profile {
+        debug!("Using profile `{profile}`");
+    }
+    let authors = defaults.authors;
+    if authors.is_empty() {
+        warn!(
+            "No author could be determined: pass `--author` or set `authors` in the configuration"
+        );
+    }
+    let mut builder = ProjectBuilder::new(registry, title, &authors).variables(defaults.variables);
+    if let Some(template) = template.or(defaults.template) {
+        builder = builder.template(&template);
+    }
+    if let Some(subtitle) = subtitle {
+        builder = builder.subtitle(&subtitle);
+    }
+    builder
+}
+
+/// Returns the authors of the `--authors-file` or, if none is given, of the `--author` arguments
+fn cli_authors(
+    output: OutputFormat,
+    authors: Vec<Author>,
+    authors_file: Option<String>,
+) -> Vec<Author> {
+    match authors_file {
+        Some(path) => config::read_authors(Path::new(&path))
+            .unwrap_or_else(|err| fail(output, "Could not read authors", &err)),
+        None => authors,
+    }
+}
+
+/// Prints the IDs of all configured templates
+fn list_templates(templates: &[Template]) {
+    println!(
+        "=== Templates ===\n{}",
+        templates
+            .iter()
+            .map(|template| format!(" - {}", template.id))
+            .collect::<Vec<String>>()
+            .join("\n")
+    );
+}
+
+/// Prints a setting's value: strings are printed as-is whilst
+/// sections and arrays are printed as TOML under their key
+fn print_setting(key: &str, value: &toml::Value) {
+    match value {
+        toml::Value::String(string) => println!("{string}"),
+        toml::Value::Table(_) | toml::Value::Array(_) => {
+            let name = key.rsplit('.').next().unwrap_or(key);
+            let mut wrapper = toml::value::Table::new();
+            wrapper.insert(name.to_string(), value.clone());
+            print!(
+                "{}",
+                toml::to_string(&wrapper).expect("Settings must always be encodable")
+            );
+        }
+        other => println!("{other}"),
+    }
+}
+
+/// Applies the command line overrides to the configured build settings
+fn build_config(
+    mut config: BuildConfig,
+    engine: Option<String>,
+    output_dir: Option<String>,
+) -> BuildConfig {
+    if let Some(engine) = engine {
+        config.engine = engine;
+    }
+    if let Some(output_dir) = output_dir {
+        config.output_dir = output_dir;
     }
+    config
 }
@@ -0,0 +1,35 @@
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use vitex::error::Report;
+
+/// How the results of commands are presented
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable log messages
+    Text,
+    /// Structured results and errors on stdout, log messages on stderr
+    Json,
+}
+
+impl OutputFormat {
+    /// Prints the result of a successful command (only in JSON mode)
+    pub fn success(self, result: Value) {
+        if self == Self::Json {
+            println!("{}", json!({ "status": "ok", "result": result }));
+        }
+    }
+
+    /// Prints the error which caused a command to fail (only in JSON mode).
+    /// Details are only included if they are not `null`
+    pub fn error(self, report: &Report, details: Value) {
+        if self != Self::Json {
+            return;
+        }
+        let mut error = report.to_json();
+        if !details.is_null() {
+            error["details"] = details;
+        }
+        println!("{}", json!({ "status": "error", "error": error }));
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+
+use crate::{config::Author, templates::AuthorsManifest};
+
+/// Derives the template variables of the authors:
+/// `author` joins all authors using the template's format and separators,
+/// `author_<n>_<field>` (starting at 1) holds the fields of the individual authors
+/// and `author_count` holds their number
+pub(super) fn author_variables(
+    authors: &[Author],
+    manifest: &AuthorsManifest,
+) -> BTreeMap<String, String> {
+    let mut variables = BTreeMap::new();
+    variables.insert("author".to_string(), join(authors, manifest));
+    variables.insert("author_count".to_string(), authors.len().to_string());
+    for (index, author) in authors.iter().enumerate() {
+        for field in Author::FIELDS {
+            variables.insert(
+                format!("author_{}_{field}", index + 1),
+                author.field(field).unwrap_or_default().to_string(),
+            );
+        }
+    }
+    variables
+}
+
+/// Formats each author and joins them, e.g. `Alice, Bob and Carol`
+fn join(authors: &[Author], manifest: &AuthorsManifest) -> String {
+    let formatted: Vec<String> = authors
+        .iter()
+        .map(|author| {
+            Author::FIELDS
+                .iter()
+                .fold(manifest.format.clone(), |format, field| {
+                    format.replace(
+                        &format!("{{{field}}}"),
+                        author.field(field).unwrap_or_default(),
+                    )
+                })
+        })
+        .collect();
+    match formatted.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!(
+            "{}{}{last}",
+            rest.join(&manifest.separator),
+            manifest.last_separator
+        ),
+    }
+}
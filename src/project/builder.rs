@@ -0,0 +1,114 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{Author, BuildConfig, CopyConfig},
+    templates::TemplateRegistry,
+};
+
+use super::{create, errors::Error, init, ConflictPolicy};
+
+/// Creates projects from the templates of a registry.
+/// Unless specified otherwise, the first template is used
+/// and neither a subtitle nor a git repository is created
+pub struct ProjectBuilder<'r> {
+    registry: &'r TemplateRegistry,
+    title: String,
+    authors: Vec<Author>,
+    template: Option<String>,
+    subtitle: Option<String>,
+    variables: BTreeMap<String, String>,
+    init_git: bool,
+    output_dir: String,
+    copy_config: CopyConfig,
+}
+
+impl<'r> ProjectBuilder<'r> {
+    pub fn new(registry: &'r TemplateRegistry, title: &str, authors: &[Author]) -> Self {
+        Self {
+            registry,
+            title: title.to_string(),
+            authors: authors.to_vec(),
+            template: None,
+            subtitle: None,
+            variables: BTreeMap::new(),
+            init_git: false,
+            output_dir: BuildConfig::default().output_dir,
+            copy_config: CopyConfig::default(),
+        }
+    }
+
+    /// Selects the template with the given ID
+    pub fn template(mut self, id: &str) -> Self {
+        self.template = Some(id.to_string());
+        self
+    }
+
+    pub fn subtitle(mut self, subtitle: &str) -> Self {
+        self.subtitle = Some(subtitle.to_string());
+        self
+    }
+
+    /// Values for additional `VITEX_<KEY>_PLACEHOLDER` placeholders of the template.
+    /// The title, subtitle and author always take precedence
+    pub fn variables(mut self, variables: BTreeMap<String, String>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Whether the project is initialized as a git repository
+    pub fn git(mut self, init_git: bool) -> Self {
+        self.init_git = init_git;
+        self
+    }
+
+    /// The build output directory (relative to the project) which git ignores
+    pub fn output_dir(mut self, output_dir: &str) -> Self {
+        self.output_dir = output_dir.to_string();
+        self
+    }
+
+    /// How the template's files are copied into the project
+    pub fn copy_config(mut self, copy_config: CopyConfig) -> Self {
+        self.copy_config = copy_config;
+        self
+    }
+
+    /// Creates the project in a new directory (named after the title) inside `parent`
+    /// and returns its path
+    pub fn create(&self, parent: &Path) -> Result<PathBuf, Error> {
+        create(
+            self.registry.templates(),
+            self.template.as_deref(),
+            &self.title,
+            &self.authors,
+            self.subtitle.as_deref(),
+            &self.variables,
+            self.registry.paths(),
+            parent,
+            self.init_git,
+            &self.output_dir,
+            &self.copy_config,
+        )
+    }
+
+    /// Applies the template to an existing directory using the given conflict policy
+    pub fn init(&self, directory: &Path, policy: ConflictPolicy) -> Result<(), Error> {
+        init(
+            self.registry.templates(),
+            self.template.as_deref(),
+            &self.title,
+            &self.authors,
+            self.subtitle.as_deref(),
+            &self.variables,
+            self.registry.paths(),
+            directory,
+            policy,
+            self.init_git,
+            &self.output_dir,
+            &self.copy_config,
+        )
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, info, warn};
+
+use crate::{
+    config::{CleanConfig, Template},
+    templates::{Manifest, TemplatePaths},
+};
+
+use super::{errors::Error, provenance::METADATA_DIR, Provenance};
+
+/// The extensions of auxiliary files which are always removed
+const DEFAULT_EXTENSIONS: [&str; 28] = [
+    "aux",
+    "log",
+    "toc",
+    "lof",
+    "lot",
+    "lol",
+    "out",
+    "fls",
+    "fdb_latexmk",
+    "synctex.gz",
+    "synctex(busy)",
+    "bbl",
+    "blg",
+    "bcf",
+    "run.xml",
+    "nav",
+    "snm",
+    "vrb",
+    "xdv",
+    "dvi",
+    "idx",
+    "ilg",
+    "ind",
+    "glo",
+    "gls",
+    "glg",
+    "ist",
+    "auxlock",
+];
+
+/// Removes the auxiliary files of the project at `project_path`.
+/// Only files whose basename matches a `.tex` file in the same directory
+/// (or anywhere in the project if the file is inside the build output directory) are removed.
+/// Returns the paths of the removed files (or the ones which would be removed)
+pub fn clean(
+    config: &CleanConfig,
+    output_dir: &str,
+    templates: &[Template],
+    templates_path: &TemplatePaths,
+    project_path: &Path,
+    all: bool,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let read_err = |err| Error::IORead {
+        path: project_path.to_string_lossy().to_string(),
+        io_error: err,
+    };
+    // Collect the extensions from the defaults, the template manifest and the config
+    let mut extensions: Vec<String> = DEFAULT_EXTENSIONS.map(str::to_string).to_vec();
+    // Templates may come from untrusted repositories: their extensions must never match sources
+    for extension in template_manifest(templates, templates_path, project_path)
+        .clean
+        .extensions
+        .into_iter()
+        .chain(config.extensions.iter().cloned())
+    {
+        match CleanConfig::is_protected(&extension) {
+            true => warn!("Ignoring clean extension `{extension}`: it matches source files"),
+            false => extensions.push(extension),
+        }
+    }
+    if all {
+        extensions.push("pdf".to_string());
+    }
+    let mut files = vec![];
+    list_files(project_path, &mut files).map_err(read_err)?;
+    // The basenames of the project's `.tex` files
+    let stems: HashSet<PathBuf> = files
+        .iter()
+        .filter_map(|file| strip_extension(file, "tex"))
+        .collect();
+    let output_path = project_path.join(output_dir);
+    let mut removed = vec![];
+    for file in files {
+        let is_auxiliary = extensions.iter().any(|extension| {
+            strip_extension(&file, extension.trim_start_matches('.'))
+                .map(|stem| stem_matches(&stem, &stems, &output_path))
+                .unwrap_or(false)
+        });
+        if !is_auxiliary {
+            continue;
+        }
+        if dry_run {
+            info!("Would remove `{}`", file.to_string_lossy());
+        } else {
+            debug!("Removing `{}`", file.to_string_lossy());
+            fs::remove_file(&file).map_err(|err| Error::IoWrite {
+                path: file.to_string_lossy().to_string(),
+                io_error: err,
+            })?;
+        }
+        removed.push(file);
+    }
+    Ok(removed)
+}
+
+/// Whether the basename of an auxiliary file matches a `.tex` file in the same directory.
+/// Inside the build output directory, any `.tex` file of the project matches
+fn stem_matches(stem: &Path, stems: &HashSet<PathBuf>, output_path: &Path) -> bool {
+    stems.contains(stem)
+        || (stem.starts_with(output_path)
+            && stems
+                .iter()
+                .any(|tex_stem| tex_stem.file_name() == stem.file_name()))
+}
+
+/// Returns the path without the given extension if it has this extension
+fn strip_extension(path: &Path, extension: &str) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.strip_suffix(extension)?.strip_suffix('.')?;
+    match stem.is_empty() {
+        true => None,
+        false => Some(path.with_file_name(stem)),
+    }
+}
+
+/// Recursively lists all files of the project, skipping git and vitex metadata
+fn list_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" || entry.file_name() == METADATA_DIR {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            list_files(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Reads the manifest of the template the project was created from.
+/// Falls back to the default manifest if the template is unknown
+fn template_manifest(
+    templates: &[Template],
+    templates_path: &TemplatePaths,
+    project_path: &Path,
+) -> Manifest {
+    let template = Provenance::read(project_path).ok().and_then(|provenance| {
+        templates
+            .iter()
+            .find(|template| template.id == provenance.template.id)
+    });
+    match template {
+        Some(template) => {
+            Manifest::read(&templates_path.locate(template).0).unwrap_or_else(|err| {
+                debug!(
+                    "Could not read manifest of template `{}`: {err}",
+                    template.id
+                );
+                Manifest::default()
+            })
+        }
+        None => Manifest::default(),
+    }
+}
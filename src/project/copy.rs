@@ -0,0 +1,259 @@
+use std::{
+    error,
+    fmt::Display,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use log::{debug, warn};
+
+use crate::{
+    config::{CopyConfig, SymlinkPolicy},
+    error::{Category, Diagnose},
+    templates::{Manifest, TemplateIgnore},
+};
+
+#[derive(Debug)]
+pub enum CopyError {
+    IO(io::Error),
+    ExternalSymlink { path: String, target: String },
+}
+
+impl Display for CopyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::IO(_) => "Could not read the template's files".to_string(),
+                Self::ExternalSymlink { path, target } =>
+                    format!("Symlink `{path}` points outside of the template (to `{target}`)"),
+            }
+        )
+    }
+}
+
+impl error::Error for CopyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            Self::ExternalSymlink { .. } => None,
+        }
+    }
+}
+
+impl Diagnose for CopyError {
+    fn category(&self) -> Category {
+        match self {
+            Self::IO(_) => Category::IO,
+            Self::ExternalSymlink { .. } => Category::TemplateValidation,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            Self::IO(_) => None,
+            Self::ExternalSymlink { .. } => Some(
+                "set `copy.external_symlinks = \"dereference\"` to copy its target instead"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for CopyError {
+    fn from(err: io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+/// The files in which vitex replaces placeholders, which must never be hardlinked
+const EDITED_FILES: [&str; 2] = ["preamble/config.tex", "main.tex"];
+
+/// A file of a template which is copied into projects
+pub struct TemplateFile {
+    /// The path relative to the template root
+    pub path: PathBuf,
+    /// The target of a symlink which is preserved when copying
+    pub symlink: Option<PathBuf>,
+    /// Whether the file may be hardlinked instead of copied
+    pub hardlink: bool,
+}
+
+/// Recursively lists all files of the template at `root` which are not excluded.
+/// Relative symlinks which stay within the template are preserved,
+/// other symlinks are refused or dereferenced depending on the config
+pub fn list_files(
+    root: &Path,
+    manifest: &Manifest,
+    config: &CopyConfig,
+) -> Result<Vec<TemplateFile>, CopyError> {
+    let ignore = TemplateIgnore::read(root)?;
+    let mut files = vec![];
+    collect_files(
+        root,
+        Path::new(""),
+        &ignore,
+        config,
+        &mut vec![],
+        &mut files,
+    )?;
+    files.sort_by(|left, right| left.path.cmp(&right.path));
+    // Mark the immutable assets which may be hardlinked
+    if config.hardlink_assets {
+        let assets = manifest.assets.matcher(root)?;
+        for file in files.iter_mut().filter(|file| {
+            file.symlink.is_none()
+                && !EDITED_FILES
+                    .iter()
+                    .any(|edited| file.path == Path::new(edited))
+        }) {
+            file.hardlink = assets
+                .matched_path_or_any_parents(&file.path, false)
+                .is_ignore();
+        }
+    }
+    Ok(files)
+}
+
+fn collect_files(
+    root: &Path,
+    relative: &Path,
+    ignore: &TemplateIgnore,
+    config: &CopyConfig,
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<TemplateFile>,
+) -> Result<(), CopyError> {
+    // Dereferenced symlinks may lead back to a directory which is currently being copied
+    let canonical = fs::canonicalize(root.join(relative))?;
+    if ancestors.contains(&canonical) {
+        warn!(
+            "Skipping `{}`: symlink loop detected",
+            relative.to_string_lossy()
+        );
+        return Ok(());
+    }
+    ancestors.push(canonical);
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        let mut file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            if stays_within(relative, &target) {
+                if !ignore.is_excluded(&path, false) {
+                    files.push(TemplateFile {
+                        path,
+                        symlink: Some(target),
+                        hardlink: false,
+                    });
+                }
+                continue;
+            }
+            match config.external_symlinks {
+                SymlinkPolicy::Refuse => {
+                    return Err(CopyError::ExternalSymlink {
+                        path: path.to_string_lossy().to_string(),
+                        target: target.to_string_lossy().to_string(),
+                    })
+                }
+                SymlinkPolicy::Dereference => file_type = fs::metadata(entry.path())?.file_type(),
+            }
+        }
+        if ignore.is_excluded(&path, file_type.is_dir()) {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_files(root, &path, ignore, config, ancestors, files)?;
+        } else if file_type.is_file() {
+            files.push(TemplateFile {
+                path,
+                symlink: None,
+                hardlink: false,
+            });
+        } else {
+            warn!(
+                "Skipping `{}`: sockets, FIFOs and devices cannot be copied",
+                path.to_string_lossy()
+            );
+        }
+    }
+    ancestors.pop();
+    Ok(())
+}
+
+/// Whether a symlink inside `directory` (relative to the template root) with the given target
+/// resolves to a path within the template
+fn stays_within(directory: &Path, target: &Path) -> bool {
+    if target.is_absolute() {
+        return false;
+    }
+    let mut depth: usize = 0;
+    for component in directory.join(target).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::ParentDir if depth == 0 => return false,
+            Component::ParentDir => depth -= 1,
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Copies a single file of the template at `root`, creating its parent directories if required.
+/// Symlinks are recreated and the permissions of regular files are preserved.
+/// Regular files are reflinked (copy-on-write) where the filesystem supports it
+/// and immutable assets are hardlinked if enabled
+pub fn copy_file(root: &Path, file: &TemplateFile, dst: &Path) -> io::Result<()> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Never write through an existing symlink
+    if fs::symlink_metadata(dst).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        fs::remove_file(dst)?;
+    }
+    let src = root.join(&file.path);
+    match &file.symlink {
+        #[cfg(unix)]
+        Some(target) => {
+            if dst.exists() {
+                fs::remove_file(dst)?;
+            }
+            std::os::unix::fs::symlink(target, dst)?;
+        }
+        _ => {
+            if dst.exists() {
+                fs::remove_file(dst)?;
+            }
+            if file.hardlink {
+                match fs::hard_link(&src, dst) {
+                    Ok(()) => return Ok(()),
+                    // Hardlinks are impossible across filesystems
+                    Err(err) => debug!(
+                        "Could not hardlink `{}`, copying instead: {err}",
+                        file.path.to_string_lossy()
+                    ),
+                }
+            }
+            reflink_copy::reflink_or_copy(&src, dst)?;
+            fs::set_permissions(dst, fs::metadata(&src)?.permissions())?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a file of a project without modifying other links to it.
+/// Hardlinked assets are replaced by a copy so that the template's file stays untouched
+pub fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        use std::os::unix::fs::MetadataExt;
+        if metadata.is_file() && metadata.nlink() > 1 {
+            fs::remove_file(path)?;
+            fs::write(path, contents)?;
+            return fs::set_permissions(path, metadata.permissions());
+        }
+    }
+    fs::write(path, contents)
+}
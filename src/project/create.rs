@@ -1,68 +1,44 @@
-use std::{fmt::Display, fs, io, path::Path};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use log::{info, warn};
 
 use crate::{
-    config::Template,
-    templates::{TemplatePaths, ValidateError, REPLACE_KEYS},
+    config::{Author, CopyConfig, Template},
+    templates::{AuthorsManifest, Manifest, TemplatePaths},
 };
 
-pub enum Error {
-    UnknownTemplate(String),
-    NoTemplates,
-    Validate(ValidateError),
-    IORead { path: String, io_error: io::Error },
-    IoWrite { path: String, io_error: io::Error },
-    DirExists(String),
-}
-
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::IORead { path, io_error } =>
-                    format!("Could not read file (at `{path}`): {io_error}"),
-                Self::IoWrite { path, io_error } =>
-                    format!("Could not write to file (at `{path}`): {io_error}"),
-                Self::Validate(err) => format!("Cannot use invalid project: {err}"),
-                Self::UnknownTemplate(id) => format!("Template `{id}` is invalid"),
-                Self::NoTemplates => "There are currently 0 templates.\nAt least 1 template is required to use this tool".to_string(),
-                Self::DirExists(path) => format!("Will not create project directory at `{path}`: directory already exists")
-            }
-        )
-    }
-}
-
-impl From<ValidateError> for Error {
-    fn from(err: ValidateError) -> Self {
-        Self::Validate(err)
-    }
-}
+use super::{
+    authors::author_variables,
+    copy::{self, CopyError, TemplateFile},
+    errors::Error,
+    git,
+    provenance::{Provenance, METADATA_DIR, PROVENANCE_FILE},
+    ConflictPolicy,
+};
 
-/// Creates a new project
+/// Creates a new project and returns its path
+#[allow(clippy::too_many_arguments)]
 pub fn create(
-    templates: &Vec<Template>,
+    templates: &[Template],
     template_id: Option<&str>,
     title: &str,
-    author: &str,
+    authors: &[Author],
     subtitle: Option<&str>,
+    variables: &BTreeMap<String, String>,
     templates_path: &TemplatePaths,
     destination: &Path,
-) -> Result<(), Error> {
-    // Check if there are templates
-    if templates.is_empty() {
-        return Err(Error::NoTemplates);
-    }
+    init_git: bool,
+    output_dir: &str,
+    copy_config: &CopyConfig,
+) -> Result<PathBuf, Error> {
     // Find the correct template
-    let template_id = template_id.unwrap_or_else(|| &templates[0].id);
-    let template = match templates.iter().find(|template| template.id == template_id) {
-        Some(found) => found,
-        None => return Err(Error::UnknownTemplate(template_id.to_string())),
-    };
+    let template = resolve_template(templates, template_id, templates_path)?;
     // Check if the path already exists
-    let destination = destination.join(title.replace(' ', "_").replace('/', "\\"));
+    let destination = destination.join(directory_name(title));
     if destination.exists() {
         return Err(Error::DirExists(
             destination
@@ -71,54 +47,260 @@ pub fn create(
                 .to_string(),
         ));
     }
-    // Validate the template in order to sort out some errors
-    let (template_path, repo_path) = match template.git.repository.is_empty() {
-        true => (templates_path.custom.join(&template.id), None),
-        false => (
-            templates_path
-                .cloned
-                .join(&template.id)
-                .join(&template.git.path_prefix),
-            Some(templates_path.cloned.join(&template.id)),
+    // Copy the entire project to the destination and replace all the placeholders
+    let written = render(
+        &template,
+        &destination,
+        ConflictPolicy::Abort,
+        &placeholder_values(
+            title,
+            subtitle,
+            authors,
+            &template.manifest.authors,
+            variables,
         ),
-    };
+        copy_config,
+    )?;
+    if init_git {
+        git::init_repository(
+            &destination,
+            &written,
+            authors.first(),
+            &template.manifest,
+            output_dir,
+        )?;
+    }
+    info!(
+        "Created new {} project at `./{}`",
+        template.template.id,
+        destination.to_str().expect("Path should be a String")
+    );
+    Ok(destination)
+}
+
+/// Combines the title, subtitle and authors with the additional variables.
+/// The subtitle defaults to the title
+pub(super) fn placeholder_values(
+    title: &str,
+    subtitle: Option<&str>,
+    authors: &[Author],
+    authors_manifest: &AuthorsManifest,
+    variables: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut values = variables.clone();
+    values.extend(author_variables(authors, authors_manifest));
+    for (key, value) in [("title", title), ("subtitle", subtitle.unwrap_or(title))] {
+        values.insert(key.to_string(), value.to_string());
+    }
+    values
+}
+
+/// Derives the name of a project's directory from its title
+pub(super) fn directory_name(title: &str) -> String {
+    title.replace(' ', "_").replace('/', "\\")
+}
 
+/// A validated template and the location of its files
+pub(super) struct ResolvedTemplate<'t> {
+    pub template: &'t Template,
+    /// The directory which holds the template's files
+    pub path: PathBuf,
+    /// The root of the template's repository (only present for git templates)
+    pub repo_path: Option<PathBuf>,
+    pub manifest: Manifest,
+}
+
+/// Finds the template with the given id (or the first one) and validates it
+pub(super) fn resolve_template<'t>(
+    templates: &'t [Template],
+    template_id: Option<&str>,
+    templates_path: &TemplatePaths,
+) -> Result<ResolvedTemplate<'t>, Error> {
+    // Check if there are templates
+    if templates.is_empty() {
+        return Err(Error::NoTemplates);
+    }
+    // Find the correct template
+    let template_id = template_id.unwrap_or_else(|| &templates[0].id);
+    let template = match templates.iter().find(|template| template.id == template_id) {
+        Some(found) => found,
+        None => return Err(Error::UnknownTemplate(template_id.to_string())),
+    };
+    // Locate the template's files
+    let (template_path, repo_path) = templates_path.locate(template);
     // Validate the template
     template.validate(&template_path, repo_path.as_ref())?;
+    let manifest = Manifest::read(&template_path).map_err(|err| Error::IORead {
+        path: template_path
+            .to_str()
+            .expect("Path should be a String")
+            .to_string(),
+        io_error: err,
+    })?;
+    Ok(ResolvedTemplate {
+        template,
+        path: template_path,
+        repo_path,
+        manifest,
+    })
+}
 
-    // Copy the entire project to the destination
-    if let Err(err) = copy_dir_all(&template_path, &destination) {
-        return Err(Error::IoWrite {
-            path: destination
-                .to_str()
-                .expect("Path should be a String")
-                .to_string(),
-            io_error: err,
-        });
+/// Copies the template's files into the destination whilst resolving conflicts
+/// with existing files using the given policy.
+/// Afterwards, the placeholders are replaced in the written `config.tex` or `main.tex`
+/// and the project's provenance is recorded.
+/// Returns the paths of all written files relative to the destination
+pub(super) fn render(
+    template: &ResolvedTemplate,
+    destination: &Path,
+    policy: ConflictPolicy,
+    variables: &BTreeMap<String, String>,
+    copy_config: &CopyConfig,
+) -> Result<Vec<PathBuf>, Error> {
+    let write_err = |err: io::Error| Error::IoWrite {
+        path: destination
+            .to_str()
+            .expect("Path should be a String")
+            .to_string(),
+        io_error: err,
+    };
+    let template_path = &template.path;
+    let files = copy::list_files(template_path, &template.manifest, copy_config).map_err(
+        |err| match err {
+            CopyError::IO(err) => Error::IORead {
+                path: template_path
+                    .to_str()
+                    .expect("Path should be a String")
+                    .to_string(),
+                io_error: err,
+            },
+            err => Error::Copy(err),
+        },
+    )?;
+    // Decide where each file is written to
+    let mut targets: Vec<(TemplateFile, PathBuf)> = Vec::with_capacity(files.len());
+    let mut conflicts = vec![];
+    for file in files {
+        let target = destination.join(&file.path);
+        if !target.exists() {
+            targets.push((file, target));
+            continue;
+        }
+        match policy {
+            ConflictPolicy::Abort => conflicts.push(
+                target
+                    .to_str()
+                    .expect("Path should be a String")
+                    .to_string(),
+            ),
+            ConflictPolicy::Skip => {
+                info!("Skipping existing file `{}`", file.path.to_string_lossy())
+            }
+            ConflictPolicy::Overwrite => {
+                warn!(
+                    "Overwriting existing file `{}`",
+                    file.path.to_string_lossy()
+                );
+                targets.push((file, target));
+            }
+            ConflictPolicy::New => {
+                let mut new_name = target.clone().into_os_string();
+                new_name.push(".new");
+                info!(
+                    "Writing template version of existing file `{}` to `{}.new`",
+                    file.path.to_string_lossy(),
+                    file.path.to_string_lossy()
+                );
+                targets.push((file, PathBuf::from(new_name)));
+            }
+        }
+    }
+    // An existing provenance belongs to another project which must stay updatable
+    let provenance_path = Path::new(METADATA_DIR).join(PROVENANCE_FILE);
+    let write_provenance = match (destination.join(&provenance_path).exists(), policy) {
+        (false, _) => true,
+        (true, ConflictPolicy::Abort) => {
+            conflicts.push(
+                destination
+                    .join(&provenance_path)
+                    .to_str()
+                    .expect("Path should be a String")
+                    .to_string(),
+            );
+            false
+        }
+        (true, ConflictPolicy::Skip | ConflictPolicy::New) => {
+            info!(
+                "Keeping the existing provenance `{}`",
+                provenance_path.to_string_lossy()
+            );
+            false
+        }
+        (true, ConflictPolicy::Overwrite) => {
+            warn!(
+                "Overwriting the existing provenance `{}`",
+                provenance_path.to_string_lossy()
+            );
+            true
+        }
+    };
+    if !conflicts.is_empty() {
+        conflicts.sort();
+        return Err(Error::Conflicts(conflicts));
+    }
+    // Copy the files to the destination
+    fs::create_dir_all(destination).map_err(write_err)?;
+    for (file, target) in &targets {
+        copy::copy_file(template_path, file, target).map_err(write_err)?;
     }
     // Replace all the placeholders
-    let config_tex_path = destination.join("preamble").join("config.tex");
-    let main_tex_path = destination.join("main.tex");
-    if config_tex_path.exists() {
-        replace_placeholders_in_file(&config_tex_path, title, subtitle.unwrap_or(title), author)?;
-    } else if main_tex_path.exists() {
-        replace_placeholders_in_file(&main_tex_path, title, subtitle.unwrap_or(title), author)?;
-    } else {
+    let config_tex_path = Path::new("preamble").join("config.tex");
+    let main_tex_path = PathBuf::from("main.tex");
+    let target_of = |path: &Path| {
+        targets
+            .iter()
+            .find(|(file, _)| file.path == path)
+            .map(|(file, target)| (file.symlink.is_some(), target))
+    };
+    if let Some((is_symlink, target)) =
+        target_of(&config_tex_path).or_else(|| target_of(&main_tex_path))
+    {
+        // Replacing the placeholders must never modify the symlink's target
+        match is_symlink {
+            true => warn!(
+                "Not replacing placeholders in `{}`: file is a symlink",
+                target.to_string_lossy()
+            ),
+            false => replace_placeholders_in_file(target, variables)?,
+        }
+    } else if !destination.join(config_tex_path).exists()
+        && !destination.join(main_tex_path).exists()
+    {
         warn!("Project contains no `main.tex` or `preamble/config.tex`")
     }
-    info!(
-        "Created new {template_id} project at `./{}`",
-        destination.to_str().expect("Path should be a String")
-    );
-    Ok(())
+    let mut written: Vec<PathBuf> = targets
+        .iter()
+        .map(|(_, target)| target.strip_prefix(destination).unwrap_or(target).into())
+        .collect();
+    // Record how the project was created
+    if write_provenance {
+        Provenance::new(
+            template.template,
+            &template.manifest,
+            template.repo_path.as_deref(),
+            variables,
+        )
+        .write(destination)
+        .map_err(write_err)?;
+        written.push(provenance_path);
+    }
+    Ok(written)
 }
 
-/// Replaces the title, subtitle and author placeholders in a file
+/// Replaces the placeholders of all variables in a file
 fn replace_placeholders_in_file(
     file_path: &Path,
-    title: &str,
-    subtitle: &str,
-    author: &str,
+    variables: &BTreeMap<String, String>,
 ) -> Result<(), Error> {
     // Read the raw file contents
     let raw_config_tex = match fs::read_to_string(file_path) {
@@ -134,14 +316,8 @@ fn replace_placeholders_in_file(
         }
     };
     // Write the contents to the file whilst replacing them
-    if let Err(err) = fs::write(
-        file_path,
-        // Replace all keys in the file
-        raw_config_tex
-            .replace(REPLACE_KEYS[1], subtitle)
-            .replace(REPLACE_KEYS[0], title)
-            .replace(REPLACE_KEYS[2], author),
-    ) {
+    if let Err(err) = copy::write_file(file_path, replace_placeholders(&raw_config_tex, variables))
+    {
         return Err(Error::IoWrite {
             path: file_path
                 .to_str()
@@ -153,18 +329,16 @@ fn replace_placeholders_in_file(
     Ok(())
 }
 
-/// Like `cp -r` on Unix platforms:
-/// Recursively copies a directory and all its contents
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        }
-    }
-    Ok(())
+/// Replaces the `VITEX_<KEY>_PLACEHOLDER` of every variable in the given file contents
+pub(super) fn replace_placeholders(contents: &str, variables: &BTreeMap<String, String>) -> String {
+    variables
+        .iter()
+        .fold(contents.to_string(), |contents, (key, value)| {
+            contents.replace(&placeholder(key), value)
+        })
+}
+
+/// Returns the placeholder of a variable (e.g. `VITEX_TITLE_PLACEHOLDER` for `title`)
+fn placeholder(key: &str) -> String {
+    format!("VITEX_{}_PLACEHOLDER", key.to_uppercase())
 }
@@ -0,0 +1,124 @@
+use std::{error, fmt::Display, io};
+
+use crate::{
+    error::{Category, Diagnose},
+    templates::ValidateError,
+};
+
+use super::copy::CopyError;
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownTemplate(String),
+    NoTemplates,
+    Validate(ValidateError),
+    Copy(CopyError),
+    IORead { path: String, io_error: io::Error },
+    IoWrite { path: String, io_error: io::Error },
+    DirExists(String),
+    Conflicts(Vec<String>),
+    NoProvenance { path: String, io_error: io::Error },
+    NotUpdatable(String),
+    Git(String),
+    MergeConflicts(Vec<String>),
+    UnknownKey { key: String, path: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::IORead { path, .. } => format!("Could not read file (at `{path}`)"),
+                Self::IoWrite { path, .. } => format!("Could not write to file (at `{path}`)"),
+                Self::Validate(_) => "Cannot use invalid project".to_string(),
+                Self::Copy(_) => "Cannot copy the template".to_string(),
+                Self::UnknownTemplate(id) => format!("Template `{id}` is invalid"),
+                Self::NoTemplates => "There are currently 0 templates.\nAt least 1 template is required to use this tool".to_string(),
+                Self::DirExists(path) => format!("Will not create project directory at `{path}`: directory already exists"),
+                Self::Conflicts(paths) => format!(
+                    "Will not overwrite {} existing file(s):\n{}",
+                    paths.len(),
+                    list(paths)
+                ),
+                Self::NoProvenance { path, .. } => format!("Could not read project provenance (at `{path}`)"),
+                Self::NotUpdatable(id) => format!("Project uses template `{id}` which has no recorded git commit: only synced git templates can be updated"),
+                Self::Git(message) => format!("Git error: {message}"),
+                Self::UnknownKey { key, path } => format!("Could not find a value for `{key}` in `{path}`"),
+                Self::MergeConflicts(paths) => format!(
+                    "Merge left conflicts in {} file(s):\n{}",
+                    paths.len(),
+                    list(paths)
+                ),
+            }
+        )
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Validate(err) => Some(err),
+            Self::Copy(err) => Some(err),
+            Self::IORead { io_error, .. }
+            | Self::IoWrite { io_error, .. }
+            | Self::NoProvenance { io_error, .. } => Some(io_error),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnose for Error {
+    fn category(&self) -> Category {
+        match self {
+            Self::Validate(err) => err.category(),
+            Self::Copy(err) => err.category(),
+            Self::IORead { .. } | Self::IoWrite { .. } => Category::IO,
+            Self::NoTemplates => Category::Config,
+            Self::Git(_) => Category::Git,
+            Self::MergeConflicts(_) => Category::Conflict,
+            Self::UnknownTemplate(_)
+            | Self::DirExists(_)
+            | Self::Conflicts(_)
+            | Self::NoProvenance { .. }
+            | Self::NotUpdatable(_)
+            | Self::UnknownKey { .. } => Category::UserInput,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        Some(
+            match self {
+                Self::Validate(err) => return err.hint(),
+                Self::Copy(err) => return err.hint(),
+                Self::UnknownTemplate(_) => "run `vitex templates list` to see the available templates",
+                Self::NoTemplates => "add a template to the configuration",
+                Self::DirExists(_) => "choose another title or use `vitex project init` to apply the template to the existing directory",
+                Self::Conflicts(_) => "use `--on-conflict` to skip, overwrite or write `.new` copies",
+                Self::NoProvenance { .. } => "projects created by versions of vitex without `.vitex/project.toml` cannot be updated: create a new project and move your changes into it",
+                Self::UnknownKey { .. } => "the value is expected to be defined using `\\def\\document<key>{...}`",
+                Self::MergeConflicts(_) => "resolve the conflict markers manually",
+                Self::IORead { .. } | Self::IoWrite { .. } | Self::NotUpdatable(_) | Self::Git(_) => {
+                    return None
+                }
+            }
+            .to_string(),
+        )
+    }
+}
+
+impl From<ValidateError> for Error {
+    fn from(err: ValidateError) -> Self {
+        Self::Validate(err)
+    }
+}
+
+/// Formats paths as a bullet list
+fn list(paths: &[String]) -> String {
+    paths
+        .iter()
+        .map(|path| format!(" - {path}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
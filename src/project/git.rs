@@ -0,0 +1,186 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use log::{debug, info, warn};
+
+use crate::{config::Author, templates::Manifest};
+
+use super::errors::Error;
+
+/// The ignore patterns for LaTeX build artifacts which are used if the template has no `.gitignore`
+const GITIGNORE: &str = "\
+# LaTeX auxiliary files
+*.aux
+*.bbl
+*.bcf
+*.blg
+*.fdb_latexmk
+*.fls
+*.glg
+*.glo
+*.gls
+*.idx
+*.ilg
+*.ind
+*.lof
+*.log
+*.lot
+*.nav
+*.out
+*.run.xml
+*.snm
+*.synctex.gz
+*.synctex(busy)
+*.toc
+*.vrb
+*.xdv
+
+# Build output
+*.dvi
+*.pdf
+build/
+";
+
+/// Initializes a git repository in the project, writes a `.gitignore` and creates an initial commit
+/// of the `written` files (relative to the project).
+/// If git has no identity configured, the (first) author is used as the commit author
+/// or, without an author's email address, the commit is skipped.
+/// If the project already is the root of a git work tree, the files are only staged
+pub(super) fn init_repository(
+    project_path: &Path,
+    written: &[PathBuf],
+    author: Option<&Author>,
+    manifest: &Manifest,
+    output_dir: &str,
+) -> Result<(), Error> {
+    // Projects inside an unrelated repository (e.g. dotfiles) get their own repository
+    let existing = match (
+        git(project_path, &["rev-parse", "--show-toplevel"]),
+        fs::canonicalize(project_path),
+    ) {
+        (Ok(toplevel), Ok(project_path)) => Path::new(toplevel.trim()) == project_path,
+        _ => false,
+    };
+    if !existing {
+        debug!("Initializing git repository...");
+        git(project_path, &["init", "--quiet"])?;
+    }
+    write_gitignore(project_path, &manifest.git.ignore, output_dir)?;
+    // Only stage what was written, never unrelated files of an existing repository.
+    // The template's files are tracked even if they are ignored (e.g. PDF figures)
+    let mut args = vec!["add", "--force", "--", ".gitignore"];
+    args.extend(
+        written
+            .iter()
+            .map(|path| path.to_str().expect("Path should be a String")),
+    );
+    git(project_path, &args)?;
+    if existing {
+        info!("Staged project files in the existing git repository");
+        return Ok(());
+    }
+    // Fall back to the author if git has no identity
+    let configured = |key| {
+        !git(project_path, &["config", key])
+            .unwrap_or_default()
+            .trim()
+            .is_empty()
+    };
+    let has_identity = configured("user.name") && configured("user.email");
+    let identity = author.and_then(|author| {
+        Some((
+            format!("user.name={}", author.name),
+            format!("user.email={}", author.email.as_deref()?),
+        ))
+    });
+    let mut args = vec![];
+    match (has_identity, &identity) {
+        (true, _) => {}
+        (false, Some((name, email))) => {
+            debug!("Git has no identity: committing as `{name}`");
+            args.extend(["-c", name, "-c", email]);
+        }
+        (false, None) => {
+            warn!("Git has no identity and no author with an email address is set: the project files are staged but not committed\nHINT: set `user.name` and `user.email` using `git config --global` and commit manually");
+            return Ok(());
+        }
+    }
+    args.extend(["commit", "--quiet", "--message", "Initial commit"]);
+    git(project_path, &args)?;
+    info!("Initialized git repository with an initial commit");
+    Ok(())
+}
+
+/// Writes the default `.gitignore` (unless the template provided one)
+/// and appends the build output and the template's additional patterns if they are missing
+fn write_gitignore(
+    project_path: &Path,
+    patterns: &[String],
+    output_dir: &str,
+) -> Result<(), Error> {
+    let path = project_path.join(".gitignore");
+    let mut contents = match path.exists() {
+        true => fs::read_to_string(&path).map_err(|err| Error::IORead {
+            path: path.to_string_lossy().to_string(),
+            io_error: err,
+        })?,
+        false => GITIGNORE.to_string(),
+    };
+    let output_dir = output_dir.trim_matches('/');
+    let mut build = vec![];
+    // `build`, `build/` and `/build/` all ignore the output directory
+    if !output_dir.is_empty()
+        && !contents
+            .lines()
+            .any(|line| line.trim().trim_matches('/') == output_dir)
+    {
+        build.push(format!("{output_dir}/"));
+    }
+    build.push("*.pdf".to_string());
+    append_missing(&mut contents, "Build output", &build);
+    append_missing(&mut contents, "Template", patterns);
+    fs::write(&path, contents).map_err(|err| Error::IoWrite {
+        path: path.to_string_lossy().to_string(),
+        io_error: err,
+    })
+}
+
+/// Appends the patterns which are not yet part of the `.gitignore` below a heading
+fn append_missing(contents: &mut String, heading: &str, patterns: &[String]) {
+    let missing: Vec<&String> = patterns
+        .iter()
+        .filter(|pattern| !contents.lines().any(|line| line.trim() == pattern.trim()))
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&format!("\n# {heading}\n"));
+    for pattern in missing {
+        contents.push_str(pattern);
+        contents.push('\n');
+    }
+}
+
+/// Runs a git command in the project and returns its stdout
+fn git(project_path: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_path)
+        .args(args)
+        .output()
+        .map_err(|err| Error::Git(format!("could not run git: {err}")))?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,74 @@
+use std::{collections::BTreeMap, path::Path};
+
+use log::info;
+
+use crate::{
+    config::{Author, CopyConfig, Template},
+    templates::TemplatePaths,
+};
+
+use super::{
+    create::{placeholder_values, render, resolve_template},
+    errors::Error,
+    git,
+};
+
+/// How files of the template which already exist in the destination are handled
+#[derive(Clone, Copy)]
+pub enum ConflictPolicy {
+    /// Do not apply the template if any file would be overwritten
+    Abort,
+    /// Keep the existing files
+    Skip,
+    /// Replace the existing files with the template's version
+    Overwrite,
+    /// Write the template's version next to the existing file using a `.new` suffix
+    New,
+}
+
+/// Applies a template to an existing directory (which is created if missing)
+#[allow(clippy::too_many_arguments)]
+pub fn init(
+    templates: &[Template],
+    template_id: Option<&str>,
+    title: &str,
+    authors: &[Author],
+    subtitle: Option<&str>,
+    variables: &BTreeMap<String, String>,
+    templates_path: &TemplatePaths,
+    destination: &Path,
+    policy: ConflictPolicy,
+    init_git: bool,
+    output_dir: &str,
+    copy_config: &CopyConfig,
+) -> Result<(), Error> {
+    let template = resolve_template(templates, template_id, templates_path)?;
+    let written = render(
+        &template,
+        destination,
+        policy,
+        &placeholder_values(
+            title,
+            subtitle,
+            authors,
+            &template.manifest.authors,
+            variables,
+        ),
+        copy_config,
+    )?;
+    if init_git {
+        git::init_repository(
+            destination,
+            &written,
+            authors.first(),
+            &template.manifest,
+            output_dir,
+        )?;
+    }
+    info!(
+        "Applied {} template to `{}`",
+        template.template.id,
+        destination.to_str().expect("Path should be a String")
+    );
+    Ok(())
+}
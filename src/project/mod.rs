@@ -1,3 +1,21 @@
+mod authors;
+mod builder;
+mod clean;
+pub(crate) mod copy;
 mod create;
+mod errors;
+mod git;
+mod init;
+mod provenance;
+mod set;
+mod update;
 
+pub use builder::ProjectBuilder;
+pub use clean::clean;
+pub use copy::CopyError;
 pub use create::create;
+pub use errors::Error;
+pub use init::{init, ConflictPolicy};
+pub use provenance::Provenance;
+pub use set::set;
+pub use update::update;
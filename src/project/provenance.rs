@@ -0,0 +1,100 @@
+use std::{collections::BTreeMap, fs, io, path::Path, process::Command};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Template, templates::Manifest};
+
+/// The directory inside a project which holds vitex-specific metadata
+pub const METADATA_DIR: &str = ".vitex";
+/// The file inside the metadata directory which records the project's provenance
+pub const PROVENANCE_FILE: &str = "project.toml";
+
+/// Records how a project was created
+#[derive(Serialize, Deserialize)]
+pub struct Provenance {
+    /// The version of vitex which created the project
+    pub vitex_version: String,
+    pub template: TemplateProvenance,
+    /// The values which were substituted for the template's placeholders
+    pub variables: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TemplateProvenance {
+    pub id: String,
+    pub repository: String,
+    pub path_prefix: String,
+    /// The commit of the template's repository (only present for git templates)
+    pub commit: Option<String>,
+    /// The file which is passed to the LaTeX engine, relative to the project root
+    #[serde(default = "default_main")]
+    pub main: String,
+}
+
+fn default_main() -> String {
+    Manifest::default().main
+}
+
+impl Provenance {
+    pub fn new(
+        template: &Template,
+        manifest: &Manifest,
+        repo_path: Option<&Path>,
+        variables: &BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            vitex_version: env!("CARGO_PKG_VERSION").to_string(),
+            template: TemplateProvenance {
+                id: template.id.clone(),
+                repository: template.git.repository.clone(),
+                path_prefix: template.git.path_prefix.clone(),
+                commit: repo_path.and_then(head_commit),
+                main: manifest.main.clone(),
+            },
+            variables: variables.clone(),
+        }
+    }
+
+    /// Reads the provenance of the project at `project_path`
+    pub fn read(project_path: &Path) -> io::Result<Self> {
+        let raw = fs::read_to_string(project_path.join(METADATA_DIR).join(PROVENANCE_FILE))?;
+        toml::from_str(&raw).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes the provenance into the metadata directory of the project at `project_path`
+    pub fn write(&self, project_path: &Path) -> io::Result<()> {
+        let metadata_path = project_path.join(METADATA_DIR);
+        fs::create_dir_all(&metadata_path)?;
+        fs::write(
+            metadata_path.join(PROVENANCE_FILE),
+            toml::to_string(self).expect("The provenance struct must always be encodable"),
+        )
+    }
+}
+
+/// Returns the commit which is currently checked out in the repository
+pub(super) fn head_commit(repo_path: &Path) -> Option<String> {
+    debug!(
+        "Determining template commit of `{}`...",
+        repo_path.to_string_lossy()
+    );
+    match Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("rev-parse")
+        .arg("HEAD")
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        _ => {
+            warn!(
+                "Could not determine the checked-out commit of `{}`: template commit is not recorded",
+                repo_path.to_string_lossy()
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{info, warn};
+
+use super::{copy::write_file, create::directory_name, errors::Error, Provenance};
+
+/// Changes a value (e.g. the title) of the project at `project_path`.
+/// The value is located by its `\def\document<key>{...}` macro or, as a fallback,
+/// by the value recorded in the project's provenance.
+/// If `rename` is set and the title is changed, the project directory is renamed accordingly.
+/// Returns the (possibly new) path of the project
+pub fn set(project_path: &Path, key: &str, value: &str, rename: bool) -> Result<PathBuf, Error> {
+    let mut provenance = match Provenance::read(project_path) {
+        Ok(provenance) => Some(provenance),
+        Err(err) => {
+            warn!("Could not read project provenance, only locating macros: {err}");
+            None
+        }
+    };
+    // The file in which the placeholders were replaced
+    let config_tex_path = project_path.join("preamble").join("config.tex");
+    let file_path = match config_tex_path.exists() {
+        true => config_tex_path,
+        false => project_path.join("main.tex"),
+    };
+    let contents = fs::read_to_string(&file_path).map_err(|err| Error::IORead {
+        path: file_path.to_string_lossy().to_string(),
+        io_error: err,
+    })?;
+    let old_value = provenance
+        .as_ref()
+        .and_then(|provenance| provenance.variables.get(key));
+    let updated = match replace_macro(&contents, key, value) {
+        Some(updated) => updated,
+        None => match old_value.and_then(|old| replace_recorded(&contents, old, value)) {
+            Some(updated) => updated,
+            None => {
+                return Err(Error::UnknownKey {
+                    key: key.to_string(),
+                    path: file_path.to_string_lossy().to_string(),
+                })
+            }
+        },
+    };
+    write_file(&file_path, updated).map_err(|err| Error::IoWrite {
+        path: file_path.to_string_lossy().to_string(),
+        io_error: err,
+    })?;
+    info!("Set `{key}` to `{value}`");
+    // Record the new value
+    if let Some(provenance) = &mut provenance {
+        provenance
+            .variables
+            .insert(key.to_string(), value.to_string());
+        provenance
+            .write(project_path)
+            .map_err(|err| Error::IoWrite {
+                path: project_path.to_string_lossy().to_string(),
+                io_error: err,
+            })?;
+    }
+    if !rename {
+        return Ok(project_path.to_path_buf());
+    }
+    if key != "title" {
+        warn!("Only changing the title renames the project directory");
+        return Ok(project_path.to_path_buf());
+    }
+    // Rename the project directory to match the new title
+    let absolute = project_path.canonicalize().map_err(|err| Error::IORead {
+        path: project_path.to_string_lossy().to_string(),
+        io_error: err,
+    })?;
+    let destination = absolute
+        .parent()
+        .expect("Project directory is expected to have a parent")
+        .join(directory_name(value));
+    if destination == absolute {
+        return Ok(project_path.to_path_buf());
+    }
+    if destination.exists() {
+        return Err(Error::DirExists(destination.to_string_lossy().to_string()));
+    }
+    fs::rename(&absolute, &destination).map_err(|err| Error::IoWrite {
+        path: destination.to_string_lossy().to_string(),
+        io_error: err,
+    })?;
+    info!(
+        "Renamed project directory to `{}`",
+        destination.to_string_lossy()
+    );
+    Ok(destination)
+}
+
+/// Replaces the argument of `\def\document<key>{...}` with the value.
+/// Returns `None` if the macro is not defined
+fn replace_macro(contents: &str, key: &str, value: &str) -> Option<String> {
+    let definition = format!("\\def\\document{key}{{");
+    let start = contents.find(&definition)? + definition.len();
+    // Find the matching closing brace
+    let mut depth = 1;
+    let mut escaped = false;
+    for (index, char) in contents[start..].char_indices() {
+        match char {
+            '\\' => {
+                escaped = !escaped;
+                continue;
+            }
+            '{' if !escaped => depth += 1,
+            '}' if !escaped => {
+                depth -= 1;
+                if depth == 0 {
+                    let end = start + index;
+                    return Some(format!("{}{value}{}", &contents[..start], &contents[end..]));
+                }
+            }
+            _ => {}
+        }
+        escaped = false;
+    }
+    None
+}
+
+/// Replaces the recorded value if it occurs exactly once as a brace group (`{value}`)
+fn replace_recorded(contents: &str, old: &str, value: &str) -> Option<String> {
+    let group = format!("{{{old}}}");
+    match contents.matches(&group).count() {
+        1 => Some(contents.replacen(&group, &format!("{{{value}}}"), 1)),
+        _ => None,
+    }
+}
@@ -0,0 +1,528 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use log::{debug, info, warn};
+
+use crate::{
+    config::Template,
+    templates::{Manifest, TemplateIgnore, TemplatePaths, IGNORE_FILE},
+};
+
+use super::{
+    copy::write_file,
+    create::replace_placeholders,
+    errors::Error,
+    provenance::{head_commit, Provenance},
+};
+
+/// The rendered files of a template revision, indexed by their path relative to the project
+type Rendered = BTreeMap<PathBuf, Vec<u8>>;
+
+/// Upgrades the project at `project_path` to the currently synced revision of its template.
+/// Changes between the recorded and the current template revision are merged into the project
+pub fn update(
+    templates: &[Template],
+    templates_path: &TemplatePaths,
+    project_path: &Path,
+) -> Result<(), Error> {
+    let mut provenance = Provenance::read(project_path).map_err(|err| Error::NoProvenance {
+        path: project_path
+            .to_str()
+            .expect("Path should be a String")
+            .to_string(),
+        io_error: err,
+    })?;
+    let template = match templates
+        .iter()
+        .find(|template| template.id == provenance.template.id)
+    {
+        Some(found) => found,
+        None => return Err(Error::UnknownTemplate(provenance.template.id)),
+    };
+    let old_commit = match &provenance.template.commit {
+        Some(commit) if !template.git.repository.is_empty() => commit.clone(),
+        _ => return Err(Error::NotUpdatable(template.id.clone())),
+    };
+    // Validate the template in order to sort out some errors
+    let repo_path = templates_path.cloned.join(&template.id);
+    let template_path = repo_path.join(&template.git.path_prefix);
+    template.validate(&template_path, Some(&repo_path))?;
+    let new_commit = head_commit(&repo_path).ok_or_else(|| {
+        Error::Git(format!(
+            "could not determine the checked-out commit of template `{}`",
+            template.id
+        ))
+    })?;
+    if new_commit == old_commit && provenance.template.path_prefix == template.git.path_prefix {
+        info!(
+            "Project is already up to date with template `{}`.",
+            template.id
+        );
+        return Ok(());
+    }
+    // Render both revisions using the recorded variables
+    debug!("Rendering template `{}` at {old_commit}...", template.id);
+    let base = render_revision(
+        &repo_path,
+        &old_commit,
+        &provenance.template.path_prefix,
+        &provenance.variables,
+    )?;
+    debug!("Rendering template `{}` at {new_commit}...", template.id);
+    let theirs = render_revision(
+        &repo_path,
+        &new_commit,
+        &template.git.path_prefix,
+        &provenance.variables,
+    )?;
+    // Merge the changes into the project
+    // A fresh private directory, so that other users cannot tamper with the merged files
+    let merge_dir = tempfile::Builder::new()
+        .prefix("vitex-update-")
+        .tempdir()
+        .map_err(|err| Error::IoWrite {
+            path: env::temp_dir().to_string_lossy().to_string(),
+            io_error: err,
+        })?;
+    let labels = [
+        "project".to_string(),
+        format!("{}@{}", template.id, short(&old_commit)),
+        format!("{}@{}", template.id, short(&new_commit)),
+    ];
+    let paths: BTreeSet<&PathBuf> = base.keys().chain(theirs.keys()).collect();
+    let mut conflicts = vec![];
+    let mut result = Ok(());
+    for path in paths {
+        if let Err(err) = update_file(
+            project_path,
+            path,
+            base.get(path),
+            theirs.get(path),
+            merge_dir.path(),
+            &labels,
+            &mut conflicts,
+        ) {
+            result = Err(err);
+            break;
+        }
+    }
+    let merge_path = merge_dir.path().to_path_buf();
+    if let Err(err) = merge_dir.close() {
+        warn!(
+            "Could not remove temporary merge directory `{}`: {err}",
+            merge_path.to_string_lossy()
+        );
+    }
+    result?;
+    // Record the new template revision
+    provenance.vitex_version = env!("CARGO_PKG_VERSION").to_string();
+    provenance.template.commit = Some(new_commit.clone());
+    provenance.template.repository = template.git.repository.clone();
+    provenance.template.path_prefix = template.git.path_prefix.clone();
+    provenance.template.main = Manifest::read(&template_path)
+        .map_err(|err| Error::IORead {
+            path: template_path.to_string_lossy().to_string(),
+            io_error: err,
+        })?
+        .main;
+    provenance
+        .write(project_path)
+        .map_err(|err| Error::IoWrite {
+            path: project_path.to_string_lossy().to_string(),
+            io_error: err,
+        })?;
+    if !conflicts.is_empty() {
+        return Err(Error::MergeConflicts(conflicts));
+    }
+    info!(
+        "Updated project to template `{}` at {}",
+        template.id,
+        short(&new_commit)
+    );
+    Ok(())
+}
+
+/// Applies the template's changes of a single file to the project
+fn update_file(
+    project_path: &Path,
+    path: &Path,
+    base: Option<&Vec<u8>>,
+    theirs: Option<&Vec<u8>>,
+    merge_dir: &Path,
+    labels: &[String; 3],
+    conflicts: &mut Vec<String>,
+) -> Result<(), Error> {
+    let target = project_path.join(path);
+    let display = path.to_string_lossy().to_string();
+    let write_err = |err| Error::IoWrite {
+        path: target.to_string_lossy().to_string(),
+        io_error: err,
+    };
+    let ours = match target.exists() {
+        true => Some(fs::read(&target).map_err(|err| Error::IORead {
+            path: target.to_string_lossy().to_string(),
+            io_error: err,
+        })?),
+        false => None,
+    };
+    match (base, theirs, ours) {
+        // The template did not change this file or the project already contains the change
+        (Some(base), Some(theirs), _) if base == theirs => {}
+        (_, Some(theirs), Some(ours)) if ours == *theirs => {}
+        // The file was deleted in the project
+        (Some(_), Some(_), None) => {
+            info!("Skipping `{display}`: file was deleted from the project")
+        }
+        // The file was added to the template
+        (None, Some(theirs), None) => {
+            info!("Adding `{display}`");
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(write_err)?;
+            }
+            write_file(&target, theirs).map_err(write_err)?;
+        }
+        // The file was removed from the template
+        (Some(base), None, Some(ours)) => {
+            if *base == ours {
+                info!("Removing `{display}`");
+                fs::remove_file(&target).map_err(write_err)?;
+            } else {
+                warn!("Keeping `{display}`: file was removed from the template but modified in the project");
+            }
+        }
+        (_, None, _) => {}
+        // The file was modified in the template and possibly in the project
+        (base, Some(theirs), Some(ours)) => {
+            if base == Some(&ours) {
+                info!("Updating `{display}`");
+                write_file(&target, theirs).map_err(write_err)?;
+                return Ok(());
+            }
+            let empty = vec![];
+            let base = base.unwrap_or(&empty);
+            match merge_file(merge_dir, &ours, base, theirs, labels)? {
+                Some((merged, clean)) => {
+                    write_file(&target, merged).map_err(write_err)?;
+                    if clean {
+                        info!("Merged `{display}`");
+                    } else {
+                        warn!("Merged `{display}` with conflicts");
+                        conflicts.push(display);
+                    }
+                }
+                None => {
+                    // Files which cannot be merged are placed next to the project's version
+                    let mut new_name = target.clone().into_os_string();
+                    new_name.push(".new");
+                    warn!(
+                        "Could not merge `{display}`: writing template version to `{display}.new`"
+                    );
+                    fs::write(&new_name, theirs).map_err(write_err)?;
+                    conflicts.push(display);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Performs a three-way merge using `git merge-file`.
+/// Returns the merged contents and whether the merge was free of conflicts
+/// or `None` if the files cannot be merged (e.g. binary files)
+fn merge_file(
+    merge_dir: &Path,
+    ours: &[u8],
+    base: &[u8],
+    theirs: &[u8],
+    labels: &[String; 3],
+) -> Result<Option<(Vec<u8>, bool)>, Error> {
+    let mut paths = vec![];
+    for (name, contents) in [("ours", ours), ("base", base), ("theirs", theirs)] {
+        let path = merge_dir.join(name);
+        fs::write(&path, contents).map_err(|err| Error::IoWrite {
+            path: path.to_string_lossy().to_string(),
+            io_error: err,
+        })?;
+        paths.push(path);
+    }
+    let output = Command::new("git")
+        .arg("merge-file")
+        .arg("-p")
+        .args(["-L", &labels[0], "-L", &labels[1], "-L", &labels[2]])
+        .args(&paths)
+        .output()
+        .map_err(|err| Error::Git(format!("could not run `git merge-file`: {err}")))?;
+    // The exit code is the number of conflicts or negative on failure
+    Ok(match output.status.code() {
+        Some(0) => Some((output.stdout, true)),
+        Some(1..=127) => Some((output.stdout, false)),
+        _ => None,
+    })
+}
+
+/// Renders the template files at the given commit with the placeholders replaced
+fn render_revision(
+    repo_path: &Path,
+    commit: &str,
+    path_prefix: &str,
+    variables: &BTreeMap<String, String>,
+) -> Result<Rendered, Error> {
+    // Git rejects empty pathspecs, so templates at the repository root list `.`
+    let pathspec = match path_prefix.is_empty() {
+        true => ".",
+        false => path_prefix,
+    };
+    let output = git(
+        repo_path,
+        &["ls-tree", "-r", "-z", "--name-only", commit, "--", pathspec],
+    )?;
+    // Apply the ignore rules of the revision
+    let ignore_path = Path::new(path_prefix).join(IGNORE_FILE);
+    let ignore_contents = git(
+        repo_path,
+        &[
+            "cat-file",
+            "blob",
+            &format!("{commit}:{}", ignore_path.to_string_lossy()),
+        ],
+    )
+    .unwrap_or_default();
+    let ignore = TemplateIgnore::parse(
+        &repo_path.join(path_prefix),
+        &String::from_utf8_lossy(&ignore_contents),
+    )
+    .map_err(|err| Error::IORead {
+        path: ignore_path.to_string_lossy().to_string(),
+        io_error: err,
+    })?;
+    let mut rendered = Rendered::new();
+    for name in String::from_utf8_lossy(&output).split('\0') {
+        if name.is_empty() {
+            continue;
+        }
+        let relative = match Path::new(name).strip_prefix(path_prefix) {
+            Ok(relative) if !ignore.is_excluded(relative, false) => relative.to_path_buf(),
+            _ => continue,
+        };
+        let contents = git(
+            repo_path,
+            &["cat-file", "blob", &format!("{commit}:{name}")],
+        )?;
+        rendered.insert(relative, contents);
+    }
+    // Replace the placeholders just like `project new` would
+    let config_tex_path = Path::new("preamble").join("config.tex");
+    let main_tex_path = PathBuf::from("main.tex");
+    let placeholder_file = match rendered.contains_key(&config_tex_path) {
+        true => config_tex_path,
+        false => main_tex_path,
+    };
+    if let Some(contents) = rendered.get_mut(&placeholder_file) {
+        *contents =
+            replace_placeholders(&String::from_utf8_lossy(contents), variables).into_bytes();
+    }
+    Ok(rendered)
+}
+
+/// Runs a git command in the repository and returns its stdout
+fn git(repo_path: &Path, args: &[&str]) -> Result<Vec<u8>, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .map_err(|err| Error::Git(format!("could not run git: {err}")))?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "`git {}` failed in `{}`: {}",
+            args.join(" "),
+            repo_path.to_string_lossy(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Abbreviates a commit hash for display purposes
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `update_file` on `main.tex` of a project containing `ours`.
+    /// Returns the resulting project file and the recorded conflicts
+    fn update(
+        base: Option<&str>,
+        theirs: Option<&str>,
+        ours: Option<&str>,
+    ) -> (Option<String>, Vec<String>) {
+        let project = tempfile::tempdir().expect("Temporary directory should be created");
+        let merge_dir = tempfile::tempdir().expect("Temporary directory should be created");
+        let path = Path::new("main.tex");
+        if let Some(ours) = ours {
+            fs::write(project.path().join(path), ours).expect("Project file should be written");
+        }
+        let labels = ["project", "old", "new"].map(String::from);
+        let mut conflicts = vec![];
+        update_file(
+            project.path(),
+            path,
+            base.map(|base| base.as_bytes().to_vec()).as_ref(),
+            theirs.map(|theirs| theirs.as_bytes().to_vec()).as_ref(),
+            merge_dir.path(),
+            &labels,
+            &mut conflicts,
+        )
+        .expect("File should be updated");
+        (
+            fs::read_to_string(project.path().join(path)).ok(),
+            conflicts,
+        )
+    }
+
+    fn unchanged(contents: &str) -> (Option<String>, Vec<String>) {
+        (Some(contents.to_string()), vec![])
+    }
+
+    #[test]
+    fn keeps_files_without_template_changes() {
+        assert_eq!(
+            update(Some("a\n"), Some("a\n"), Some("b\n")),
+            unchanged("b\n")
+        );
+        assert_eq!(
+            update(Some("a\n"), Some("b\n"), Some("b\n")),
+            unchanged("b\n")
+        );
+        assert_eq!(update(None, None, Some("b\n")), unchanged("b\n"));
+    }
+
+    #[test]
+    fn adds_new_template_files() {
+        assert_eq!(update(None, Some("a\n"), None), unchanged("a\n"));
+    }
+
+    #[test]
+    fn skips_files_deleted_from_the_project() {
+        assert_eq!(update(Some("a\n"), Some("b\n"), None), (None, vec![]));
+    }
+
+    #[test]
+    fn removes_only_unmodified_files() {
+        assert_eq!(update(Some("a\n"), None, Some("a\n")), (None, vec![]));
+        assert_eq!(update(Some("a\n"), None, Some("b\n")), unchanged("b\n"));
+    }
+
+    #[test]
+    fn updates_unmodified_files() {
+        assert_eq!(
+            update(Some("a\n"), Some("b\n"), Some("a\n")),
+            unchanged("b\n")
+        );
+    }
+
+    #[test]
+    fn merges_modified_files() {
+        assert_eq!(
+            update(
+                Some("one\ntwo\nthree\n"),
+                Some("one\ntwo\nTHREE\n"),
+                Some("ONE\ntwo\nthree\n")
+            ),
+            unchanged("ONE\ntwo\nTHREE\n")
+        );
+    }
+
+    #[test]
+    fn records_merge_conflicts() {
+        let (contents, conflicts) = update(Some("a\n"), Some("b\n"), Some("c\n"));
+        assert_eq!(
+            contents,
+            Some("<<<<<<< project\nc\n=======\nb\n>>>>>>> new\n".to_string())
+        );
+        assert_eq!(conflicts, vec!["main.tex".to_string()]);
+    }
+
+    /// Creates a repository with a template below `prefix` and returns it with its commit
+    fn repository(prefix: &str) -> (tempfile::TempDir, String) {
+        let repo = tempfile::tempdir().expect("Temporary directory should be created");
+        let root = repo.path().join(prefix);
+        fs::create_dir_all(root.join("preamble")).expect("Template should be created");
+        fs::write(root.join("main.tex"), "\\input{preamble/config}\n")
+            .expect("Template should be written");
+        fs::write(
+            root.join("preamble").join("config.tex"),
+            "\\title{VITEX_TITLE_PLACEHOLDER}\n",
+        )
+        .expect("Template should be written");
+        fs::write(root.join("notes.txt"), "").expect("Template should be written");
+        fs::write(root.join(IGNORE_FILE), "notes.txt\n").expect("Template should be written");
+        fs::write(repo.path().join("README.md"), "").expect("Template should be written");
+        for args in [
+            &["init", "-q"][..],
+            &["add", "-A"],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.org",
+                "commit",
+                "-q",
+                "-m",
+                "Template",
+            ],
+        ] {
+            git(repo.path(), args).expect("Repository should be set up");
+        }
+        let commit = git(repo.path(), &["rev-parse", "HEAD"]).expect("Commit should exist");
+        let commit = String::from_utf8_lossy(&commit).trim().to_string();
+        (repo, commit)
+    }
+
+    fn rendered_files(prefix: &str) -> Rendered {
+        let (repo, commit) = repository(prefix);
+        let variables = BTreeMap::from([("title".to_string(), "Thesis".to_string())]);
+        render_revision(repo.path(), &commit, prefix, &variables)
+            .expect("Revision should be rendered")
+    }
+
+    #[test]
+    fn renders_templates_in_subdirectories() {
+        let rendered = rendered_files("templates/thesis");
+        assert_eq!(
+            rendered.keys().collect::<Vec<_>>(),
+            [
+                Path::new("main.tex"),
+                &Path::new("preamble").join("config.tex"),
+            ]
+        );
+        assert_eq!(
+            rendered[&Path::new("preamble").join("config.tex")],
+            b"\\title{Thesis}\n"
+        );
+    }
+
+    #[test]
+    fn renders_templates_at_the_repository_root() {
+        let rendered = rendered_files("");
+        assert_eq!(
+            rendered.keys().collect::<Vec<_>>(),
+            [
+                Path::new("README.md"),
+                Path::new("main.tex"),
+                &Path::new("preamble").join("config.tex"),
+            ]
+        );
+        assert_eq!(
+            rendered[&Path::new("preamble").join("config.tex")],
+            b"\\title{Thesis}\n"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::{self, Write};
+
+/// Asks a yes/no question on stderr.
+/// Without an interactive input, the answer is no
+pub fn confirm(question: &str, default: bool) -> io::Result<bool> {
+    let choices = match default {
+        true => "[Y/n]",
+        false => "[y/N]",
+    };
+    let Some(answer) = read_answer(&format!("{question} {choices} "))? else {
+        return Ok(false);
+    };
+    Ok(match answer.to_lowercase().as_str() {
+        "" => default,
+        answer => matches!(answer, "y" | "yes"),
+    })
+}
+
+/// Asks for a value on stderr. Empty answers (and a missing input) select the default
+pub fn ask(question: &str, default: &str) -> io::Result<String> {
+    let prompt = match default.is_empty() {
+        true => format!("{question}: "),
+        false => format!("{question} [{default}]: "),
+    };
+    Ok(match read_answer(&prompt)? {
+        Some(answer) if !answer.is_empty() => answer,
+        _ => default.to_string(),
+    })
+}
+
+/// Prints the prompt and reads a trimmed line (`None` if the input has ended)
+fn read_answer(prompt: &str) -> io::Result<Option<String>> {
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_string()))
+}
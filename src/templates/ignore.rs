@@ -0,0 +1,57 @@
+use std::{fs, io, path::Path};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use super::MANIFEST_FILE;
+
+/// The optional file at the root of a template which excludes files from being copied.
+/// It uses the same syntax as `.gitignore`
+pub const IGNORE_FILE: &str = ".vitexignore";
+
+/// Decides which files of a template are copied into new projects
+pub struct TemplateIgnore {
+    matcher: Gitignore,
+}
+
+impl TemplateIgnore {
+    /// Reads the ignore rules of the template at `template_path`.
+    /// Templates without an ignore file only use the built-in exclusions
+    pub fn read(template_path: &Path) -> io::Result<Self> {
+        let ignore_path = template_path.join(IGNORE_FILE);
+        let contents = match ignore_path.exists() {
+            true => fs::read_to_string(ignore_path)?,
+            false => String::new(),
+        };
+        Self::parse(template_path, &contents)
+    }
+
+    /// Parses ignore rules which are relative to `template_path`
+    pub fn parse(template_path: &Path, contents: &str) -> io::Result<Self> {
+        let mut builder = GitignoreBuilder::new(template_path);
+        for line in contents.lines() {
+            builder
+                .add_line(None, line)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+        Ok(Self {
+            matcher: builder
+                .build()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+        })
+    }
+
+    /// Whether a path (relative to the template root) must not be copied into projects.
+    /// Besides the ignore rules, this applies to the template's metadata files
+    /// and to git metadata of cloned templates
+    pub fn is_excluded(&self, relative: &Path, is_dir: bool) -> bool {
+        relative == Path::new(MANIFEST_FILE)
+            || relative == Path::new(IGNORE_FILE)
+            || relative
+                .components()
+                .any(|component| component.as_os_str() == ".git")
+            || self
+                .matcher
+                .matched_path_or_any_parents(relative, is_dir)
+                .is_ignore()
+    }
+}
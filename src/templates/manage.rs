@@ -0,0 +1,132 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use log::info;
+
+use crate::{
+    config::{self, validate_id, ConfigDocument, CopyConfig, Template},
+    project::copy::{copy_file, list_files, CopyError, TemplateFile},
+};
+
+use super::{Manifest, TemplatePaths, IGNORE_FILE, MANIFEST_FILE};
+
+/// Registers a template in the configuration file.
+/// The files of a local template are copied from `source` into the custom templates directory
+pub fn add_template(
+    config_path: &Path,
+    paths: &TemplatePaths,
+    template: &Template,
+    source: Option<&Path>,
+) -> config::Result<()> {
+    // The ID is used as a directory name below
+    validate_id(&template.id)?;
+    let mut document = ConfigDocument::open(config_path)?;
+    document.add_template(template)?;
+    let config = document.config()?;
+    // Local templates must be in place before the configuration can be validated
+    let destination = paths.custom.join(&template.id);
+    let mut copied = false;
+    if let Some(source) = source {
+        if !destination.exists() {
+            // Never leave a partial copy behind
+            if let Err(err) = copy_template(source, &destination, &config.copy) {
+                if destination.exists() {
+                    fs::remove_dir_all(&destination)?;
+                }
+                return Err(err.into());
+            }
+            copied = true;
+        } else if fs::canonicalize(source)? != fs::canonicalize(&destination)? {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!(
+                    "custom template directory `{}` already exists",
+                    destination.to_str().expect("Path should be a String")
+                ),
+            )
+            .into());
+        }
+    }
+    if let Err(err) = config.validate(&paths.custom) {
+        if copied {
+            fs::remove_dir_all(&destination)?;
+        }
+        return Err(err.into());
+    }
+    document.save()?;
+    info!("Added template `{}`", template.id);
+    Ok(())
+}
+
+/// Removes a template from the configuration file and deletes its clone.
+/// The files of local templates are kept
+pub fn remove_template(
+    config_path: &Path,
+    paths: &TemplatePaths,
+    id: &str,
+) -> config::Result<Template> {
+    let mut document = ConfigDocument::open(config_path)?;
+    let template = document
+        .config()?
+        .templates
+        .into_iter()
+        .find(|template| template.id == id)
+        .ok_or_else(|| config::Error::UnknownTemplate(id.to_string()))?;
+    document.remove_template(id)?;
+    document.save()?;
+    let (path, repo_path) = paths.locate(&template);
+    match repo_path {
+        Some(repo_path) if repo_path.exists() => {
+            fs::remove_dir_all(&repo_path)?;
+            info!("Removed template `{id}` and its clone");
+        }
+        Some(_) => info!("Removed template `{id}`"),
+        None => info!(
+            "Removed template `{id}` (its files are kept at `{}`)",
+            path.to_str().expect("Path should be a String")
+        ),
+    }
+    Ok(template)
+}
+
+/// Copies the files of a local template like they are copied into projects
+/// (respecting its `.vitexignore` and the symlink policy), including its metadata files
+fn copy_template(source: &Path, destination: &Path, config: &CopyConfig) -> Result<(), CopyError> {
+    let parent = destination
+        .parent()
+        .expect("Template directory should have a parent");
+    if fs::canonicalize(parent)?.starts_with(fs::canonicalize(source)?) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "the template cannot be copied into itself (to `{}`)",
+                destination.to_str().expect("Path should be a String")
+            ),
+        )
+        .into());
+    }
+    // Hardlinks would tie the registered template to the original files
+    let config = CopyConfig {
+        external_symlinks: config.external_symlinks,
+        hardlink_assets: false,
+    };
+    let mut files = list_files(source, &Manifest::read(source)?, &config)?;
+    // The metadata files are never copied into projects but belong to the template
+    for metadata in [MANIFEST_FILE, IGNORE_FILE] {
+        if source.join(metadata).is_file() {
+            files.push(TemplateFile {
+                path: metadata.into(),
+                symlink: None,
+                hardlink: false,
+            });
+        }
+    }
+    fs::create_dir_all(destination)?;
+    for file in &files {
+        copy_file(source, file, &destination.join(&file.path))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::{fs, io, path::Path};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The optional file at the root of a template which describes it
+pub const MANIFEST_FILE: &str = "vitex.toml";
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Manifest {
+    /// The file which is passed to the LaTeX engine, relative to the template root
+    pub main: String,
+    pub clean: CleanManifest,
+    pub git: GitManifest,
+    pub assets: AssetsManifest,
+    pub authors: AuthorsManifest,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            main: "main.tex".to_string(),
+            clean: CleanManifest::default(),
+            git: GitManifest::default(),
+            assets: AssetsManifest::default(),
+            authors: AuthorsManifest::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+pub struct CleanManifest {
+    /// Additional extensions of auxiliary files which are produced by this template
+    pub extensions: Vec<String>,
+}
+
+impl Manifest {
+    /// Reads the manifest of the template at `template_path`.
+    /// Templates without a manifest use the default values
+    pub fn read(template_path: &Path) -> io::Result<Self> {
+        let manifest_path = template_path.join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(Self::default());
+        }
+        toml::from_str(&fs::read_to_string(manifest_path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+pub struct GitManifest {
+    /// Additional patterns which are appended to the `.gitignore` of new projects
+    pub ignore: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+pub struct AssetsManifest {
+    /// Patterns (using `.gitignore` syntax) of files which are never modified in projects.
+    /// These may be hardlinked instead of copied
+    pub immutable: Vec<String>,
+}
+
+/// How the authors of a project are joined into the `author` variable
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AuthorsManifest {
+    /// The format of a single author (`{name}`, `{email}`, `{affiliation}` and `{student_id}`
+    /// are replaced with the author's fields)
+    pub format: String,
+    /// The separator between authors
+    pub separator: String,
+    /// The separator between the last two authors
+    pub last_separator: String,
+}
+
+impl Default for AuthorsManifest {
+    fn default() -> Self {
+        Self {
+            format: "{name}".to_string(),
+            separator: ", ".to_string(),
+            last_separator: " and ".to_string(),
+        }
+    }
+}
+
+impl AssetsManifest {
+    /// Builds a matcher for the immutable assets of the template at `template_path`
+    pub fn matcher(&self, template_path: &Path) -> io::Result<Gitignore> {
+        let mut builder = GitignoreBuilder::new(template_path);
+        for pattern in &self.immutable {
+            builder
+                .add_line(None, pattern)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+        builder
+            .build()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
@@ -1,7 +1,15 @@
+mod ignore;
+mod manage;
+mod manifest;
+mod registry;
 mod sync;
 mod validate;
 
-pub use sync::{purge_cloned, sync_git};
+pub use self::ignore::{TemplateIgnore, IGNORE_FILE};
+pub use manage::{add_template, remove_template};
+pub use manifest::{AuthorsManifest, Manifest, MANIFEST_FILE};
+pub use registry::TemplateRegistry;
+pub use sync::{purge_cloned, sync_git, SyncError, SyncStatus, SyncedTemplate};
 pub use validate::{validate_templates, ValidateError, REPLACE_KEYS};
 
 use std::{
@@ -17,6 +25,22 @@ pub struct TemplatePaths {
     pub cloned: PathBuf,
 }
 
+impl TemplatePaths {
+    /// Returns the directory which holds the template's files and,
+    /// for git templates, the root of the template's repository
+    pub fn locate(&self, template: &Template) -> (PathBuf, Option<PathBuf>) {
+        match template.git.repository.is_empty() {
+            true => (self.custom.join(&template.id), None),
+            false => (
+                self.cloned
+                    .join(&template.id)
+                    .join(&template.git.path_prefix),
+                Some(self.cloned.join(&template.id)),
+            ),
+        }
+    }
+}
+
 pub fn create_templates_directory(custom: &PathBuf, cloned: &PathBuf) -> io::Result<()> {
     // Custom tempplates
     if !custom.exists() {
@@ -50,14 +74,3 @@ pub fn template_paths(base_path: &Path) -> io::Result<TemplatePaths> {
         cloned: cloned_templates_path,
     })
 }
-
-pub fn list_templates(templates: &[Template]) {
-    println!(
-        "=== Templates ===\n{}",
-        templates
-            .iter()
-            .map(|template| format!(" - {}", template.id))
-            .collect::<Vec<String>>()
-            .join("\n")
-    );
-}
@@ -0,0 +1,74 @@
+use std::io;
+
+use crate::config::{Config, RepositoryPolicy, Template};
+
+use super::{
+    create_templates_directory, purge_cloned, sync_git, validate_templates, SyncError,
+    SyncedTemplate, TemplatePaths, ValidateError,
+};
+
+/// The configured templates and the directories which hold their files
+pub struct TemplateRegistry {
+    templates: Vec<Template>,
+    paths: TemplatePaths,
+    policy: RepositoryPolicy,
+}
+
+impl TemplateRegistry {
+    pub fn new(templates: Vec<Template>, paths: TemplatePaths, policy: RepositoryPolicy) -> Self {
+        Self {
+            templates,
+            paths,
+            policy,
+        }
+    }
+
+    /// Uses the templates and the repository policy of the configuration
+    pub fn from_config(config: &Config, paths: TemplatePaths) -> Self {
+        Self::new(
+            config.templates.clone(),
+            paths,
+            config.repository_policy.clone(),
+        )
+    }
+
+    pub fn templates(&self) -> &[Template] {
+        &self.templates
+    }
+
+    pub fn paths(&self) -> &TemplatePaths {
+        &self.paths
+    }
+
+    pub fn policy(&self) -> &RepositoryPolicy {
+        &self.policy
+    }
+
+    /// Returns the template with the given ID
+    pub fn get(&self, id: &str) -> Option<&Template> {
+        self.templates.iter().find(|template| template.id == id)
+    }
+
+    /// Creates the directories for custom and cloned templates if they do not exist
+    pub fn create_directories(&self) -> io::Result<()> {
+        create_templates_directory(&self.paths.custom, &self.paths.cloned)
+    }
+
+    /// Clones or pulls all git templates
+    pub fn sync(&self) -> Result<Vec<SyncedTemplate>, SyncError> {
+        sync_git(&self.templates, &self.paths.cloned, &self.policy)
+    }
+
+    /// Validates all templates which are available locally
+    pub fn validate(&self) -> Result<(), ValidateError> {
+        for path in [&self.paths.cloned, &self.paths.custom] {
+            validate_templates(&self.templates, path)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all cloned templates
+    pub fn purge(&self) -> io::Result<()> {
+        purge_cloned(&self.paths.cloned)
+    }
+}
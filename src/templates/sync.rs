@@ -1,4 +1,5 @@
 use std::{
+    error,
     fmt::Display,
     fs, io,
     path::Path,
@@ -6,15 +7,41 @@ use std::{
 };
 
 use log::{debug, info};
+use serde::Serialize;
 
-use crate::{config::Template, templates::validate::validate_templates};
+use crate::{
+    config::{RepositoryPolicy, Template},
+    error::{Category, Diagnose},
+    templates::validate::validate_templates,
+};
 
 use super::validate::ValidateError;
 
+#[derive(Debug)]
 pub enum SyncError {
     IO(io::Error),
     Git(String),
     Validate(ValidateError),
+    RepositoryRejected {
+        id: String,
+        repository: String,
+        reason: String,
+    },
+}
+
+/// The outcome of syncing a single template
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Cloned,
+    Updated,
+    UpToDate,
+}
+
+#[derive(Serialize)]
+pub struct SyncedTemplate {
+    pub id: String,
+    pub status: SyncStatus,
 }
 
 impl Display for SyncError {
@@ -23,15 +50,54 @@ impl Display for SyncError {
             f,
             "{}",
             match self {
-                SyncError::IO(err) => format!("IO error: {err}"),
-                Self::Validate(err) =>
-                    format!("post-sync template validation detected an issue:\n{err}"),
+                SyncError::IO(_) => "IO error".to_string(),
+                Self::Validate(_) => "post-sync template validation detected an issue".to_string(),
                 Self::Git(message) => format!("Git error: {message}"),
+                Self::RepositoryRejected {
+                    id,
+                    repository,
+                    reason,
+                } =>
+                    format!("Repository `{repository}` of template `{id}` is not allowed: {reason}"),
             }
         )
     }
 }
 
+impl error::Error for SyncError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            Self::Validate(err) => Some(err),
+            Self::Git(_) | Self::RepositoryRejected { .. } => None,
+        }
+    }
+}
+
+impl Diagnose for SyncError {
+    fn category(&self) -> Category {
+        match self {
+            Self::IO(_) => Category::IO,
+            Self::Git(_) => Category::Git,
+            Self::Validate(err) => err.category(),
+            Self::RepositoryRejected { .. } => Category::Config,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            Self::IO(_) => None,
+            Self::Git(_) => {
+                Some("check your network connection and access to the repository".to_string())
+            }
+            Self::Validate(err) => err.hint(),
+            Self::RepositoryRejected { .. } => {
+                Some("check the `repository_policy` section of the configuration".to_string())
+            }
+        }
+    }
+}
+
 impl From<io::Error> for SyncError {
     fn from(err: io::Error) -> Self {
         Self::IO(err)
@@ -44,24 +110,31 @@ impl From<ValidateError> for SyncError {
     }
 }
 
-pub fn sync_git(templates: &Vec<Template>, cloned_path: &Path) -> Result<(), SyncError> {
+pub fn sync_git(
+    templates: &[Template],
+    cloned_path: &Path,
+    policy: &RepositoryPolicy,
+) -> Result<Vec<SyncedTemplate>, SyncError> {
     let git_templates: Vec<&Template> = templates
         .iter()
         .filter(|template| !template.git.repository.is_empty())
         .collect();
+    let mut synced = Vec::with_capacity(git_templates.len());
     // Iterate over the git templates
     for template in &git_templates {
         // The path were the repository is located
         let repo_path = cloned_path.join(&template.id);
+        check_policy(policy, template, &template.git.repository)?;
         if !repo_path.exists() {
             debug!(
                 "Template `{}` does not exist: cloning from `{}`...",
                 template.id, template.git.repository
             );
             let output = Command::new("git")
+                .env("GIT_ALLOW_PROTOCOL", policy.git_allow_protocol())
                 .arg("clone")
                 .arg(&template.git.repository)
-                .arg(repo_path)
+                .arg(&repo_path)
                 .stderr(Stdio::inherit())
                 .output()?;
             if !output.status.success() {
@@ -70,28 +143,47 @@ pub fn sync_git(templates: &Vec<Template>, cloned_path: &Path) -> Result<(), Syn
                     template.git.repository, template.id,
                 )));
             }
-            info!("Successfully cloned template")
+            if let Some(rev) = &template.git.rev {
+                checkout(&repo_path, rev, policy)?;
+            }
+            info!("Successfully cloned template");
+            synced.push(SyncedTemplate {
+                id: template.id.clone(),
+                status: SyncStatus::Cloned,
+            });
         } else {
             debug!("Updating template `{}`...", template.id);
-            let output = Command::new("git")
+            // The clone's remote may differ from the configured repository
+            let remote = Command::new("git")
                 .arg("-C")
-                .arg(repo_path)
-                .arg("pull")
-                .stderr(Stdio::inherit())
-                .stdout(Stdio::piped())
+                .arg(&repo_path)
+                .args(["remote", "get-url", "origin"])
                 .output()?;
-            if !output.status.success() {
-                return Err(SyncError::Git(format!(
-                    "could not pull from git repo ({}) of template {}",
-                    template.git.repository, template.id,
-                )));
-            }
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if stdout.trim_end().trim_start() == "Already up to date." {
-                info!("Template `{}` is up to date.", template.id)
-            } else {
-                info!("Template `{}` was updated:\n{stdout}", template.id)
+            if remote.status.success() {
+                check_policy(
+                    policy,
+                    template,
+                    String::from_utf8_lossy(&remote.stdout).trim(),
+                )?;
             }
+            let status = match &template.git.rev {
+                Some(rev) => {
+                    let previous = git_stdout(&repo_path, &["rev-parse", "HEAD"])?;
+                    checkout(&repo_path, rev, policy)?;
+                    if git_stdout(&repo_path, &["rev-parse", "HEAD"])? == previous {
+                        info!("Template `{}` is up to date.", template.id);
+                        SyncStatus::UpToDate
+                    } else {
+                        info!("Template `{}` was updated to `{rev}`.", template.id);
+                        SyncStatus::Updated
+                    }
+                }
+                None => pull(&repo_path, template, policy)?,
+            };
+            synced.push(SyncedTemplate {
+                id: template.id.clone(),
+                status,
+            });
         }
     }
     debug!("Validating templates...");
@@ -100,9 +192,112 @@ pub fn sync_git(templates: &Vec<Template>, cloned_path: &Path) -> Result<(), Syn
         "Updated and scanned {} template(s). No issues detected.",
         &git_templates.len()
     );
+    Ok(synced)
+}
+
+/// Pulls the default branch of a template's repository
+fn pull(
+    repo_path: &Path,
+    template: &Template,
+    policy: &RepositoryPolicy,
+) -> Result<SyncStatus, SyncError> {
+    let output = Command::new("git")
+        .env("GIT_ALLOW_PROTOCOL", policy.git_allow_protocol())
+        .arg("-C")
+        .arg(repo_path)
+        .arg("pull")
+        .stderr(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .output()?;
+    if !output.status.success() {
+        return Err(SyncError::Git(format!(
+            "could not pull from git repo ({}) of template {}",
+            template.git.repository, template.id,
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(if stdout.trim_end().trim_start() == "Already up to date." {
+        info!("Template `{}` is up to date.", template.id);
+        SyncStatus::UpToDate
+    } else {
+        info!("Template `{}` was updated:\n{stdout}", template.id);
+        SyncStatus::Updated
+    })
+}
+
+/// Fetches the repository and checks out the given branch, tag or commit.
+/// Branches are resolved using the remote so that they follow its latest commit
+fn checkout(repo_path: &Path, rev: &str, policy: &RepositoryPolicy) -> Result<(), SyncError> {
+    let fetch = Command::new("git")
+        .env("GIT_ALLOW_PROTOCOL", policy.git_allow_protocol())
+        .arg("-C")
+        .arg(repo_path)
+        .args(["fetch", "--tags", "--force", "origin"])
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !fetch.status.success() {
+        return Err(SyncError::Git(format!(
+            "could not fetch `{}`",
+            repo_path.to_str().expect("Path should be a String")
+        )));
+    }
+    let commit = git_stdout(
+        repo_path,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("origin/{rev}^{{commit}}"),
+        ],
+    )
+    .or_else(|_| {
+        git_stdout(
+            repo_path,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{rev}^{{commit}}"),
+            ],
+        )
+    })
+    .map_err(|_| SyncError::Git(format!("revision `{rev}` does not exist")))?;
+    git_stdout(repo_path, &["checkout", "--quiet", "--detach", &commit])?;
     Ok(())
 }
 
+/// Runs a git command in the repository and returns its trimmed stdout
+fn git_stdout(repo_path: &Path, args: &[&str]) -> Result<String, SyncError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(SyncError::Git(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Rejects repositories which are not allowed by the policy
+fn check_policy(
+    policy: &RepositoryPolicy,
+    template: &Template,
+    repository: &str,
+) -> Result<(), SyncError> {
+    policy
+        .check(repository)
+        .map_err(|reason| SyncError::RepositoryRejected {
+            id: template.id.clone(),
+            repository: repository.to_string(),
+            reason,
+        })
+}
+
 pub fn purge_cloned(cloned_path: &Path) -> io::Result<()> {
     fs::remove_dir_all(cloned_path)?;
     info!("Successfully deleted cloned templates");
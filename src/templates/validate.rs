@@ -1,4 +1,5 @@
 use std::{
+    error,
     fmt::Display,
     fs, io,
     path::{Path, PathBuf},
@@ -6,7 +7,12 @@ use std::{
 
 use log::warn;
 
-use crate::config::Template;
+use crate::{
+    config::{CleanConfig, Template},
+    error::{Category, Diagnose},
+};
+
+use super::{Manifest, TemplateIgnore};
 
 pub const REPLACE_KEYS: [&str; 3] = [
     "VITEX_TITLE_PLACEHOLDER",
@@ -14,6 +20,7 @@ pub const REPLACE_KEYS: [&str; 3] = [
     "VITEX_AUTHOR_PLACEHOLDER",
 ];
 
+#[derive(Debug)]
 pub enum ValidateError {
     ReplaceError {
         id: String,
@@ -34,6 +41,14 @@ pub enum ValidateError {
         path: String,
         io_error: io::Error,
     },
+    Manifest {
+        id: String,
+        io_error: io::Error,
+    },
+    IgnoreFile {
+        id: String,
+        io_error: io::Error,
+    },
 }
 
 impl Display for ValidateError {
@@ -48,16 +63,53 @@ impl Display for ValidateError {
                     format!("Invalid path-prefix for template `{id}`:\nPath prefix leads to nowhere (full path: `{full_path}`)"),
                     Self::MissingConfigAndMainTex { id, full_path } =>
                     format!("Template `{id}` is missing the file `preable/config.tex` or `main.tex` (full path `{full_path}`)"),
-                Self::IORead { id, path, io_error } => format!("Could not read file at `{path}` whilst validating template `{id}`:\n{io_error}"),
-                Self::NotFound(id) => format!("Template `{id}` is set-up but not found locally:\nHINT: check if the template is present at the correct path"),
-                Self::NotCloned(id) => format!("Template `{id}` is set-up but not yet installed:\nHINT: run `vitex templates sync` to address this issue"),
+                Self::IORead { id, path, .. } => format!("Could not read file at `{path}` whilst validating template `{id}`"),
+                Self::NotFound(id) => format!("Template `{id}` is set-up but not found locally"),
+                Self::Manifest { id, .. } => format!("Template `{id}` holds a malformed manifest (at `vitex.toml`)"),
+                Self::IgnoreFile { id, .. } => format!("Template `{id}` holds a malformed ignore file (at `.vitexignore`)"),
+                Self::NotCloned(id) => format!("Template `{id}` is set-up but not yet installed"),
             }
         )
     }
 }
 
+impl error::Error for ValidateError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::IORead { io_error, .. }
+            | Self::Manifest { io_error, .. }
+            | Self::IgnoreFile { io_error, .. } => Some(io_error),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnose for ValidateError {
+    fn category(&self) -> Category {
+        match self {
+            Self::IORead { .. } => Category::IO,
+            _ => Category::TemplateValidation,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            Self::NotFound(_) => {
+                Some("check if the template is present at the correct path".to_string())
+            }
+            Self::NotCloned(_) => {
+                Some("run `vitex templates sync` to address this issue".to_string())
+            }
+            Self::PathPrefixError { .. } => {
+                Some("check the `path_prefix` of the template in the configuration".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
 pub fn validate_templates(
-    templates: &Vec<Template>,
+    templates: &[Template],
     templates_path: &Path,
 ) -> Result<(), ValidateError> {
     for template in templates {
@@ -105,6 +157,57 @@ impl Template {
                 return Err(ValidateError::NotFound(self.id.clone()));
             };
         }
+        // Test if the manifest is well-formed
+        let manifest = match Manifest::read(template_path) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                return Err(ValidateError::Manifest {
+                    id: self.id.clone(),
+                    io_error: err,
+                })
+            }
+        };
+        if let Err(err) = manifest.assets.matcher(template_path) {
+            return Err(ValidateError::Manifest {
+                id: self.id.clone(),
+                io_error: err,
+            });
+        }
+        for extension in &manifest.clean.extensions {
+            if CleanConfig::is_protected(extension) {
+                warn!(
+                    "Template `{}` declares clean extension `{extension}` which matches source files and is ignored",
+                    self.id
+                )
+            }
+        }
+        if !template_path.join(&manifest.main).exists() {
+            warn!(
+                "Template `{}` declares main file `{}` which does not exist",
+                self.id, manifest.main
+            )
+        }
+        // Test if the ignore rules exclude any of the entry files
+        let ignore = match TemplateIgnore::read(template_path) {
+            Ok(ignore) => ignore,
+            Err(err) => {
+                return Err(ValidateError::IgnoreFile {
+                    id: self.id.clone(),
+                    io_error: err,
+                })
+            }
+        };
+        let mut entries = vec![manifest.main.as_str(), "main.tex", "preamble/config.tex"];
+        entries.sort();
+        entries.dedup();
+        for entry in entries {
+            if template_path.join(entry).exists() && ignore.is_excluded(Path::new(entry), false) {
+                warn!(
+                    "Template `{}` excludes its entry file `{entry}` from being copied into projects",
+                    self.id
+                )
+            }
+        }
         // Test if the template contains a `preable/config.tex` or `main.tex`
         let config_tex_path = template_path.join("preamble").join("config.tex");
         let main_tex_path = template_path.join("main.tex");
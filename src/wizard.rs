@@ -0,0 +1,194 @@
+use std::io::{self, IsTerminal};
+
+use log::error;
+use vitex::{
+    config::{
+        detect_author, validate_id, validate_path_prefix, Author, Config, RepositoryPolicy,
+        Template, TemplateGitConfig, ValidateError,
+    },
+    error::{Diagnose, Report},
+};
+
+use crate::{cli::InitArgs, prompt};
+
+/// Whether questions can be asked on the terminal
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Parses and validates a git template given as `ID=REPOSITORY[#PATH_PREFIX]`
+pub fn parse_git_template(raw: &str) -> Result<Template, String> {
+    let (id, source) = raw
+        .split_once('=')
+        .ok_or("expected `ID=REPOSITORY[#PATH_PREFIX]`")?;
+    let (repository, path_prefix) = source.split_once('#').unwrap_or((source, ""));
+    if id.is_empty() || repository.is_empty() {
+        return Err("the ID and the repository must not be empty".to_string());
+    }
+    // Report invalid templates here instead of at the generated configuration
+    let describe = |err: ValidateError| match err.hint() {
+        Some(hint) => format!("{err} ({hint})"),
+        None => err.to_string(),
+    };
+    validate_id(id).map_err(describe)?;
+    validate_path_prefix(id, path_prefix).map_err(describe)?;
+    RepositoryPolicy::default()
+        .check(repository)
+        .map_err(|reason| {
+            ValidateError::RepositoryRejected {
+                id: id.to_string(),
+                repository: repository.to_string(),
+                reason,
+            }
+            .to_string()
+        })?;
+    Ok(Template {
+        id: id.to_string(),
+        git: TemplateGitConfig {
+            repository: repository.to_string(),
+            path_prefix: path_prefix.to_string(),
+            rev: None,
+        },
+    })
+}
+
+/// Ensures that all requested templates are built-in
+pub fn check_builtin(ids: &[String]) -> Result<(), String> {
+    let builtin = Config::default().templates;
+    match ids
+        .iter()
+        .find(|id| !builtin.iter().any(|template| &template.id == *id))
+    {
+        Some(id) => Err(format!(
+            "`{id}` is not a built-in template (available: {})",
+            builtin
+                .iter()
+                .map(|template| format!("`{}`", template.id))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Builds a configuration from the arguments and, if interactive, asks for everything they leave open.
+/// Returns the configuration and whether its templates should be synced
+pub fn setup(args: InitArgs, interactive: bool) -> io::Result<(Config, bool)> {
+    let authors = match args.author.is_empty() {
+        true => ask_author(interactive)?.into_iter().collect(),
+        false => args.author,
+    };
+    let builtin = Config::default().templates;
+    let mut templates: Vec<Template> = if args.no_templates {
+        vec![]
+    } else if !args.template.is_empty() {
+        args.template
+            .iter()
+            .filter_map(|id| builtin.iter().find(|template| &template.id == id))
+            .cloned()
+            .collect()
+    } else if interactive {
+        let mut enabled = vec![];
+        for template in builtin {
+            let question = format!("Enable the built-in template `{}`?", template.id);
+            if prompt::confirm(&question, true)? {
+                enabled.push(template);
+            }
+        }
+        enabled
+    } else {
+        builtin
+    };
+    let ask_git = interactive && args.git_template.is_empty();
+    templates.extend(args.git_template);
+    while ask_git && prompt::confirm("Add a template from a git repository?", false)? {
+        if let Some(template) = ask_git_template(&templates)? {
+            templates.push(template);
+        }
+    }
+    let has_git = templates
+        .iter()
+        .any(|template| !template.git.repository.is_empty());
+    let sync =
+        args.sync || (interactive && has_git && prompt::confirm("Clone the templates now?", true)?);
+    Ok((
+        Config {
+            authors,
+            templates,
+            ..Config::default()
+        },
+        sync,
+    ))
+}
+
+/// Asks for the user's identity (suggesting the detected one).
+/// Without questions, the detected identity is used as it is
+fn ask_author(interactive: bool) -> io::Result<Option<Author>> {
+    let detected = detect_author();
+    if !interactive {
+        return Ok(detected);
+    }
+    let detected = detected.unwrap_or_default();
+    let name = prompt::ask("Your name", &detected.name)?;
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let optional = |value: String| (!value.is_empty()).then_some(value);
+    Ok(Some(Author {
+        email: optional(prompt::ask(
+            "Your email address (optional)",
+            detected.email.as_deref().unwrap_or_default(),
+        )?),
+        affiliation: optional(prompt::ask("Your affiliation (optional)", "")?),
+        ..Author::new(&name)
+    }))
+}
+
+/// Asks for the settings of a git template until each of them is valid.
+/// An empty ID or repository cancels the template
+fn ask_git_template(templates: &[Template]) -> io::Result<Option<Template>> {
+    let invalid = |err: ValidateError| error!("{}", Report::new("Invalid template", &err));
+    let id = loop {
+        let id = prompt::ask("Template ID (empty to cancel)", "")?;
+        if id.is_empty() {
+            return Ok(None);
+        }
+        match validate_id(&id) {
+            Err(err) => invalid(err),
+            Ok(()) if templates.iter().any(|template| template.id == id) => {
+                invalid(ValidateError::DuplicateID(id))
+            }
+            Ok(()) => break id,
+        }
+    };
+    let policy = RepositoryPolicy::default();
+    let repository = loop {
+        let repository = prompt::ask("Repository (empty to cancel)", "")?;
+        if repository.is_empty() {
+            return Ok(None);
+        }
+        match policy.check(&repository) {
+            Err(reason) => invalid(ValidateError::RepositoryRejected {
+                id: id.clone(),
+                repository,
+                reason,
+            }),
+            Ok(()) => break repository,
+        }
+    };
+    let path_prefix = loop {
+        let path_prefix = prompt::ask("Directory inside the repository (optional)", "")?;
+        match validate_path_prefix(&id, &path_prefix) {
+            Err(err) => invalid(err),
+            Ok(()) => break path_prefix,
+        }
+    };
+    Ok(Some(Template {
+        id,
+        git: TemplateGitConfig {
+            repository,
+            path_prefix,
+            rev: None,
+        },
+    }))
+}